@@ -0,0 +1,152 @@
+use std::fmt::Debug;
+
+use proc_macro2::Ident;
+use quote::ToTokens;
+use syn::{
+    BinOp, Error, Expr, Result, Token, parse::{Parse, ParseStream}
+};
+
+#[derive(Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+/// The value of one binding in a case, parsed from `name = value` in `#[bind(...)]` or `#[bind_default(...)]`
+pub enum Binding {
+    /// A field of the variant, bound under its own name
+    Field {
+        /// The name of the field
+        name: Ident,
+    },
+    /// `name = expr`, optionally followed by `if guard`
+    Expr {
+        /// The name of the binding
+        name: Ident,
+        /// The value, which lookups also match against as a pattern
+        expr: Expr,
+        /// A condition on the value, checked by lookups after matching `expr`
+        guard: Option<Expr>,
+    },
+    /// Ranges and or-patterns can be matched against, but do not produce a value
+    Pattern {
+        /// The name of the binding
+        name: Ident,
+        /// The pattern, kept as the expression it was parsed from
+        pat: Expr,
+        /// A condition on the value, checked by lookups after matching `pat`
+        guard: Option<Expr>,
+    },
+    /// `flatten = field` takes every binding from a nested enum that also derives `Bind`.
+    /// Lookups without `self` skip a recursive `Box<Self>` field, so they never find values nested inside it.
+    Flatten {
+        /// Always `flatten`
+        name: Ident,
+        /// The field holding the nested enum
+        field: Ident,
+    },
+    /// `name = !`: the case has no value for the binding, even if a default gives one
+    Never {
+        /// The name of the binding
+        name: Ident,
+    },
+}
+
+impl Binding {
+    /// The name the binding is written under in `#[bind(...)]`
+    pub fn name(&self) -> &Ident {
+        match self {
+            Binding::Field { name } => name,
+            Binding::Expr { name, .. } => name,
+            Binding::Pattern { name, .. } => name,
+            Binding::Flatten { name, .. } => name,
+            Binding::Never { name } => name,
+        }
+    }
+
+    // Whether both bindings match some value in common, as far as can be told without evaluating them
+    pub(crate) fn overlaps(&self, other: &Binding) -> bool {
+        match (self, other) {
+            (Binding::Never { .. }, _) | (_, Binding::Never { .. }) => false,
+            _ if self.guard().is_some() || other.guard().is_some() => false,
+            (Binding::Expr { expr: syn::Expr::Lit(first), .. }, Binding::Expr { expr: syn::Expr::Lit(second), .. }) => first == second,
+            _ => self.is_irrefutable() || other.is_irrefutable(),
+        }
+    }
+
+    pub(crate) fn is_irrefutable(&self) -> bool {
+        match self {
+            _ if self.guard().is_some() => false,
+            Binding::Field { .. } => true,
+            Binding::Expr { expr: syn::Expr::Infer(_), .. } => true,
+            Binding::Expr { expr: syn::Expr::Path(path), .. } => path.path.get_ident()
+                .is_some_and(|ident| !ident.to_string().starts_with(char::is_uppercase)),
+            Binding::Expr { .. } | Binding::Pattern { .. } | Binding::Flatten { .. } | Binding::Never { .. } => false,
+        }
+    }
+
+    /// The `if` condition of an expression or pattern binding
+    pub fn guard(&self) -> Option<&Expr> {
+        match self {
+            Binding::Expr { guard, .. } | Binding::Pattern { guard, .. } => guard.as_ref(),
+            Binding::Field { .. } | Binding::Flatten { .. } | Binding::Never { .. } => None,
+        }
+    }
+
+    pub(crate) fn is_pattern_only(expr: &Expr) -> bool {
+        match expr {
+            Expr::Range(_) => true,
+            Expr::Binary(binary) => matches!(binary.op, BinOp::BitOr(_)),
+            _ => false,
+        }
+    }
+}
+
+impl Debug for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Field { name } => f.debug_tuple("Field")
+                .field(&name.to_string())
+                .finish(),
+            Binding::Expr { name, expr, guard } => f.debug_struct("Expr")
+                .field("name", &name.to_string())
+                .field("expr", &expr.to_token_stream().to_string())
+                .field("guard", &guard.as_ref().map(|guard| guard.to_token_stream().to_string()))
+                .finish(),
+            Binding::Pattern { name, pat, guard } => f.debug_struct("Pattern")
+                .field("name", &name.to_string())
+                .field("pat", &pat.to_token_stream().to_string())
+                .field("guard", &guard.as_ref().map(|guard| guard.to_token_stream().to_string()))
+                .finish(),
+            Binding::Flatten { name, field } => f.debug_struct("Flatten")
+                .field("name", &name.to_string())
+                .field("field", &field.to_string())
+                .finish(),
+            Binding::Never { name } => f.debug_tuple("Never")
+                .field(&name.to_string())
+                .finish(),
+        }
+    }
+}
+
+impl Parse for Binding {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name = input.parse::<Ident>()?;
+        input.parse::<Token![=]>()?;
+        if input.peek(Token![!]) {
+            input.parse::<Token![!]>()?;
+            return Ok(Binding::Never { name });
+        }
+        let expr = Expr::parse(input)?;
+        if name == "flatten" {
+            let Expr::Path(path) = &expr else { return Err(Error::new_spanned(expr, "Expected a field to flatten")); };
+            let Some(field) = path.path.get_ident() else { return Err(Error::new_spanned(expr, "Expected a field to flatten")); };
+            return Ok(Binding::Flatten { name, field: field.clone() });
+        }
+        let mut guard = None;
+        if input.peek(Token![if]) {
+            input.parse::<Token![if]>()?;
+            guard = Some(Expr::parse(input)?);
+        }
+        if Binding::is_pattern_only(&expr) {
+            return Ok(Binding::Pattern { name, pat: expr, guard });
+        }
+        Ok(Binding::Expr { name, expr, guard })
+    }
+}
@@ -0,0 +1,252 @@
+use core::panic;
+use std::{collections::BTreeMap, fmt::Debug};
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    BinOp, Error, Expr, Fields, Lit, Member, Meta, RangeLimits, Result, Token, UnOp, Variant, punctuated::Punctuated, spanned::Spanned
+};
+
+use crate::binding::Binding;
+
+/// One way of matching or building a variant: each `#[bind(...)]` on a variant is a separate case
+#[derive(Clone, PartialEq)]
+pub struct Case<'v> {
+    /// The variant the case matches and builds
+    pub variant: &'v Variant,
+    /// Every binding of the case by name, including the variant's named fields and inherited defaults
+    pub bindings: BTreeMap<Ident, Binding>,
+}
+
+impl Case<'_> {
+    pub(crate) fn gen_self_expr(&self) -> TokenStream {
+        let variant_name = &self.variant.ident;
+                
+        match self.variant.fields {
+            Fields::Named(_) => {
+                let mut field_exprs = Vec::new();
+                for field in self.variant.fields.iter() {
+                    let Some(field_name) = &field.ident else {
+                        panic!("Named field in variant {variant_name} does not have a name: {field:?}");
+                    };
+                    field_exprs.push(quote! { #field_name });
+                }
+                quote! { Self::#variant_name { #(#field_exprs),* } }
+            },
+            Fields::Unnamed(_) => {
+                let mut field_exprs = Vec::new();
+                for (i, field) in self.variant.fields.iter().enumerate() {
+                    let field_ident = format_ident!("_{i}", span = field.span());
+                    field_exprs.push(quote! { #field_ident });
+                }
+                quote! { Self::#variant_name ( #(#field_exprs),* ) }
+            },
+            Fields::Unit => {
+                quote! { Self::#variant_name }
+            },
+        }
+    }
+
+    // Like `gen_self_expr`, but skips fields not mentioned in `uses`, so matching on `*self` moves only what it needs
+    pub(crate) fn gen_self_pattern(&self, uses: &TokenStream) -> TokenStream {
+        let variant_name = &self.variant.ident;
+        let mentioned = |field: &Ident| mentions_ident(uses.clone(), field);
+        match self.variant.fields {
+            Fields::Named(_) => {
+                let field_patterns = self.variant.fields.iter().enumerate().map(|field| {
+                    let name = get_field_name(&field);
+                    if mentioned(&name) { quote! { #name } } else { quote! { #name: _ } }
+                });
+                quote! { Self::#variant_name { #(#field_patterns),* } }
+            },
+            Fields::Unnamed(_) => {
+                let field_patterns = self.variant.fields.iter().enumerate().map(|field| {
+                    let name = get_field_name(&field);
+                    if mentioned(&name) { quote! { #name } } else { quote! { _ } }
+                });
+                quote! { Self::#variant_name ( #(#field_patterns),* ) }
+            },
+            Fields::Unit => {
+                quote! { Self::#variant_name }
+            },
+        }
+    }
+}
+
+pub(crate) fn mentions_ident(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(token) => token == *ident,
+        proc_macro2::TokenTree::Group(group) => mentions_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+impl Debug for Case<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Case")
+            .field("variant", &self.variant.to_token_stream().to_string())
+            .field("bindings", &self.bindings)
+            .finish()
+    }
+}
+
+impl Case<'_> {
+    /// A flattened variant may have any binding, as far as can be told without seeing the nested enum
+    pub fn has_binding(&self, name: &Ident) -> bool {
+        self.bindings.contains_key(name) || self.flatten().is_some()
+    }
+
+    pub(crate) fn flatten(&self) -> Option<&Ident> {
+        self.bindings.values().find_map(|binding| match binding {
+            Binding::Flatten { field, .. } => Some(field),
+            _ => None,
+        })
+    }
+
+    // The field holding the value of a binding, if it is a field or an alias of one
+    pub(crate) fn binding_field(&self, name: &Ident) -> Option<&Ident> {
+        match self.bindings.get(name)? {
+            Binding::Field { name } => Some(name),
+            Binding::Expr { expr: Expr::Path(path), .. } => path.path.get_ident().filter(|ident| self.field(ident).is_some()),
+            Binding::Expr { .. } | Binding::Pattern { .. } | Binding::Flatten { .. } | Binding::Never { .. } => None,
+        }
+    }
+
+    pub(crate) fn binding_member(&self, name: &Ident) -> Option<Member> {
+        self.field_member(self.binding_field(name)?)
+    }
+
+    pub(crate) fn field_member(&self, field: &Ident) -> Option<Member> {
+        let (index, _) = self.variant.fields.iter().enumerate().find(|field_entry| get_field_name(field_entry) == *field)?;
+        match self.variant.fields {
+            Fields::Named(_) => Some(Member::Named(field.clone())),
+            _ => Some(Member::Unnamed(index.into())),
+        }
+    }
+
+    pub(crate) fn field(&self, name: &Ident) -> Option<&syn::Field> {
+        self.variant.fields.iter().enumerate()
+            .find(|field| get_field_name(field) == *name)
+            .map(|(_, field)| field)
+    }
+
+    pub(crate) fn mentions_field(&self, expr: &Expr) -> bool {
+        fn mentions(tokens: TokenStream, case: &Case) -> bool {
+            tokens.into_iter().any(|token| match token {
+                proc_macro2::TokenTree::Ident(ident) => case.field(&ident).is_some(),
+                proc_macro2::TokenTree::Group(group) => mentions(group.stream(), case),
+                _ => false,
+            })
+        }
+        mentions(expr.to_token_stream(), self)
+    }
+
+    /// The binding as written in the attribute, for bindings whose value does not come from a field
+    pub fn binding_value_string(&self, name: &Ident) -> Option<String> {
+        let (Some(Binding::Expr { expr, .. }) | Some(Binding::Pattern { pat: expr, .. })) = self.bindings.get(name) else { return None; };
+        if matches!(expr, Expr::Infer(_)) || self.mentions_field(expr) { return None; }
+        Some(value_string(expr))
+    }
+
+    // Patterns, and guarded expressions over values other than fields, can be matched against but have no value
+    pub(crate) fn is_pattern_only(&self, name: &Ident) -> bool {
+        match self.bindings.get(name) {
+            Some(Binding::Pattern { .. }) => true,
+            Some(Binding::Expr { expr, guard: Some(_), .. }) => !self.mentions_field(expr),
+            _ => false,
+        }
+    }
+
+    /// An expression for the binding's value, in scope of the variant's fields
+    pub fn gen_binding_value(&self, name: &Ident) -> Option<TokenStream> {
+        if self.is_pattern_only(name) {
+            return None;
+        }
+        match self.bindings.get(name) {
+            Some(Binding::Field { name }) => Some(quote! { #name }),
+            Some(Binding::Expr { expr, .. }) => Some(quote! { #expr }),
+            Some(Binding::Pattern { .. }) | Some(Binding::Flatten { .. }) | Some(Binding::Never { .. }) | None => None,
+        }
+    }
+}
+
+// Literals are written by value, and ranges and or-patterns from their literal ends, e.g. `500..=599`
+fn value_string(expr: &Expr) -> String {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(lit) => lit.value(),
+            Lit::Char(lit) => lit.value().to_string(),
+            Lit::Int(lit) => lit.base10_digits().to_string(),
+            Lit::Float(lit) => lit.base10_digits().to_string(),
+            lit => lit.to_token_stream().to_string(),
+        },
+        Expr::Range(range) => {
+            let limits = match range.limits {
+                RangeLimits::HalfOpen(_) => "..",
+                RangeLimits::Closed(_) => "..=",
+            };
+            let [start, end] = [&range.start, &range.end].map(|end| end.as_deref().map(value_string).unwrap_or_default());
+            format!("{start}{limits}{end}")
+        },
+        Expr::Binary(binary) if matches!(binary.op, BinOp::BitOr(_)) =>
+            format!("{} | {}", value_string(&binary.left), value_string(&binary.right)),
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => format!("-{}", value_string(&unary.expr)),
+        Expr::Group(group) => value_string(&group.expr),
+        Expr::Paren(paren) => value_string(&paren.expr),
+        expr => expr.to_token_stream().to_string(),
+    }
+}
+
+pub(crate) fn get_field_name((i, field): &(usize, &syn::Field)) -> Ident {
+    if let Some(ident) = &field.ident {
+        return ident.clone()
+    }
+    format_ident!("_{i}", span = field.span())
+}
+
+/// The cases of a variant, each starting from `defaults`, then the variant's named fields, then its `#[bind(...)]`
+pub fn get_cases<'v>(variant: &'v Variant, defaults: &[Binding]) -> Result<Vec<Case<'v>>> {
+    let variant_name = &variant.ident;
+
+    // Defaults from `#[bind_default(...)]` give way to fields, and to the variant's own bindings
+    let mut base_bindings: BTreeMap<Ident, Binding> = defaults.iter()
+        .map(|binding| (binding.name().clone(), binding.clone()))
+        .collect();
+
+    for field in variant.fields.iter().enumerate() {
+        let field_name = get_field_name(&field);
+        let binding = Binding::Field { name: field_name.clone() };
+        base_bindings.insert(field_name, binding);
+    }
+
+    let mut cases = Vec::new();
+    for attr in &variant.attrs {
+        let mut bindings = base_bindings.clone();
+        
+        if attr.path().is_ident("bind") {
+            let Meta::List(meta_list) = &attr.meta else { 
+                return Err(Error::new_spanned(attr, "Expected a list of bind = value pairs inside #[bind(...)]"));
+            };
+            let parser = Punctuated::<Binding, Token![,]>::parse_terminated;
+            for binding in meta_list.parse_args_with(parser)? {
+                let binding_name = binding.name();
+                if let Binding::Flatten { field, .. } = &binding
+                && !variant.fields.iter().enumerate().any(|f| get_field_name(&f) == *field) {
+                    return Err(Error::new_spanned(field, format!(r#"Variant "{variant_name}" has no field "{field}" to flatten"#)));
+                }
+                if variant.fields.iter().any(|f| f.ident.as_ref() == Some(binding_name)) {
+                    return Err(Error::new_spanned(binding_name, format!(r#"Variant "{variant_name}" already has a field named "{binding_name}"; #[bind(...)] cannot redefine fields"#)));
+                };
+                bindings.insert(binding_name.clone(), binding);
+            }
+        }
+
+        cases.push(Case { variant, bindings });
+    }
+
+    if cases.is_empty() {
+        cases.push(Case { variant, bindings: base_bindings });
+    }
+
+    Ok(cases)
+}
@@ -0,0 +1,1174 @@
+use core::panic;
+use std::fmt::Debug;
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Error, Expr, FnArg, LitStr, Pat, PatIdent, Result, ReturnType, Signature, Token, Type, Visibility, parenthesized, parse::{Parse, ParseStream}, parse_quote, punctuated::Punctuated
+};
+
+use crate::{binding::Binding, case::{Case, mentions_ident}, schema::SchemaSpec, types::{argument_type, binding_type, is_borrowed_type, is_copy_type, iterator_item_type, type_argument}};
+
+/// What a query returns, from `return = Mode` in `query(...)`
+#[derive(Debug, PartialEq, Clone)]
+pub enum OutputMode {
+    /// The first match, or `None`
+    Option,
+    /// The first match, where every variant must have one
+    Strict,
+    /// The first match, panicking without one
+    Unwrap,
+    /// Every match, in declaration order
+    Vec,
+    /// The first match, or an error naming the inputs
+    Result,
+    /// Every match, produced lazily by an iterator
+    Iter,
+    /// Every match, collected into the return type
+    Collect,
+    /// Whether the variant has the binding, or has it with the value of the condition
+    Bool,
+    /// Whether any case matches the arguments
+    Any,
+    /// How many cases match the arguments
+    Count,
+    /// The only match, `None` without one, or an error when several match
+    Single,
+    /// `self` with the binding's field replaced
+    Update,
+    /// Replaces the binding's field in place, returning whether the variant has one
+    Set,
+}
+
+impl Parse for OutputMode {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ident = input.parse::<Ident>()?;
+        match ident.to_string().as_str() {
+            "Option" => Ok(OutputMode::Option),
+            "Strict" => Ok(OutputMode::Strict),
+            "Unwrap" => Ok(OutputMode::Unwrap),
+            "Vec" => Ok(OutputMode::Vec),
+            "Result" => Ok(OutputMode::Result),
+            "Iter" => Ok(OutputMode::Iter),
+            "Collect" => Ok(OutputMode::Collect),
+            "Bool" => Ok(OutputMode::Bool),
+            "Any" => Ok(OutputMode::Any),
+            "Count" => Ok(OutputMode::Count),
+            "Single" => Ok(OutputMode::Single),
+            "Update" => Ok(OutputMode::Update),
+            "Set" => Ok(OutputMode::Set),
+            _ => Err(Error::new_spanned(ident,
+                "Expected 'Option', 'Strict', 'Unwrap', 'Vec', 'Result', 'Iter', 'Collect', 'Bool', 'Any', 'Count', 'Single', 'Update', or 'Set'")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum ReceiverKind { Value, Ref, RefMut }
+
+/// A query function, parsed from `query(fn ..., return = Mode(...))`
+#[derive(Debug, PartialEq)]
+pub struct FunctionSpec {
+    pub(crate) visibility: Visibility,
+    pub(crate) signature: Signature,
+    pub(crate) output_mode: OutputMode,
+    pub(crate) output_names: Vec<Ident>,
+    pub(crate) condition: Option<Expr>,
+    pub(crate) error_type: Option<Type>,
+    pub(crate) optional_args: bool,
+}
+
+impl FunctionSpec {
+    /// The signature of the generated function, with inferred types once `infer_types` has run
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// What the function returns for the cases that match
+    pub fn output_mode(&self) -> &OutputMode {
+        &self.output_mode
+    }
+
+    /// The error type from `return = Result(..., Error)`, if one is given
+    pub fn error_type(&self) -> Option<&Type> {
+        self.error_type.as_ref()
+    }
+
+    /// The function with a body matching over `cases`, using `default_error` for `Result` queries without their own
+    pub fn gen_function(&self, cases: &Vec<Case>, default_error: &Ident) -> Result<TokenStream> {
+        let body = match self.output_mode {
+            OutputMode::Strict => self.gen_body_strict(cases)?,
+            OutputMode::Unwrap => self.gen_body_unwrap(cases)?,
+            OutputMode::Option => self.gen_body_option(cases)?,
+            OutputMode::Vec => self.gen_body_vec(cases)?,
+            OutputMode::Result => self.gen_body_result(cases, default_error)?,
+            OutputMode::Iter => self.gen_body_iter(cases)?,
+            OutputMode::Collect => self.gen_body_collect(cases)?,
+            OutputMode::Bool => self.gen_body_bool(cases)?,
+            OutputMode::Any => self.gen_body_any(cases)?,
+            OutputMode::Count => self.gen_body_count(cases)?,
+            OutputMode::Single => self.gen_body_single(cases, default_error)?,
+            OutputMode::Update => self.gen_body_update(cases)?,
+            OutputMode::Set => self.gen_body_set(cases)?,
+        };
+
+        let visibility = &self.visibility;
+        let signature = &self.signature;
+        // Earlier cases take precedence, so later ones matching the same inputs are expected to be unreachable
+        Ok(quote! {
+            #[allow(unused_variables, unreachable_patterns)]
+            #visibility #signature {
+                #body
+            }
+        })
+    }
+
+    pub(crate) fn gen_body_strict(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        let (body, _) = self.gen_forwarding_match(cases, &quote! {}, &Forward::gen_value_arm)?;
+        Ok(body)
+    }
+
+    pub(crate) fn gen_body_unwrap(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        let fallback = quote! { value => panic!("Cannot determine what to return for value: {value:?}") };
+        let (body, _) = self.gen_forwarding_match(cases, &fallback, &Forward::gen_value_arm)?;
+        Ok(body)
+    }
+
+    pub(crate) fn gen_body_option(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        let (body, _) = self.gen_forwarding_match(cases, &quote! { _ => None }, &|forward, rest| {
+            let (call, wrap) = (&forward.call, &forward.wrap);
+            match &forward.pattern {
+                Some(pattern) => quote! { #pattern => #call },
+                None => {
+                    let rest = rest.unwrap_or_else(|| quote! { None });
+                    quote! {
+                        _ => match #call {
+                            Some(value) => Some(#wrap),
+                            None => #rest,
+                        }
+                    }
+                },
+            }
+        })?;
+        Ok(body)
+    }
+
+    pub(crate) fn gen_body_result(&self, cases: &Vec<Case>, default_error: &Ident) -> Result<TokenStream> {
+        let match_expr = &self.gen_match_expr();
+        let query = LitStr::new(&self.signature.ident.to_string(), self.signature.ident.span());
+        let fallback = if self.returns_self_on_error() {
+            quote! { _ => Err(self) }
+        } else {
+            quote! {
+                _ => Err(::core::convert::Into::into(#default_error::NoMatch {
+                    query: #query,
+                    inputs: format!("{:?}", #match_expr),
+                }))
+            }
+        };
+
+        // A nested error is passed on when no later case could still match
+        let (body, _) = self.gen_forwarding_match(cases, &fallback, &|forward, rest| {
+            let (call, wrap) = (&forward.call, &forward.wrap);
+            let error = match (&forward.rebuild, self.returns_self_on_error()) {
+                (Some(rebuild), true) => quote! { Err(value) => Err(#rebuild) },
+                _ => quote! { Err(error) => Err(::core::convert::Into::into(error)) },
+            };
+            match &forward.pattern {
+                Some(pattern) => quote! {
+                    #pattern => match #call {
+                        Ok(value) => Ok(#wrap),
+                        #error,
+                    }
+                },
+                None => {
+                    let error = match rest {
+                        Some(rest) => quote! { Err(_) => #rest },
+                        None => error,
+                    };
+                    quote! {
+                        _ => match #call {
+                            Ok(value) => Ok(#wrap),
+                            #error,
+                        }
+                    }
+                },
+            }
+        })?;
+        Ok(body)
+    }
+
+    // Matches the cases in order; a constructor forward calls the nested query once, and the later cases are
+    // matched only if it finds nothing. Also returns whether there was any arm besides the fallback.
+    pub(crate) fn gen_forwarding_match(&self, cases: &[Case], fallback: &TokenStream,
+                                       forward_arm: &dyn Fn(&Forward, Option<TokenStream>) -> TokenStream) -> Result<(TokenStream, bool)> {
+        let mut arms = Vec::new();
+        for (index, case) in cases.iter().enumerate() {
+            if let Some(forward) = self.gen_forward(case)? {
+                if forward.pattern.is_some() {
+                    arms.push(forward_arm(&forward, None));
+                    continue;
+                }
+                let (rest, has_arms) = self.gen_forwarding_match(&cases[index + 1..], fallback, forward_arm)?;
+                arms.push(forward_arm(&forward, has_arms.then_some(rest)));
+                break;
+            }
+            // TODO: Sink the wildcard/capturing patterns
+            let Some(pattern) = self.gen_arm_pattern(case) else { continue; };
+            let Some(output) = self.gen_output(case) else { continue; };
+            arms.push(quote! { #pattern => #output } );
+        }
+
+        let has_arms = !arms.is_empty();
+        let match_expr = &self.gen_match_expr();
+        Ok((quote! {
+            match #match_expr {
+                #(#arms,)*
+                #fallback
+            }
+        }, has_arms))
+    }
+
+    pub(crate) fn gen_body_single(&self, cases: &Vec<Case>, default_error: &Ident) -> Result<TokenStream> {
+        let match_expr = &self.gen_match_expr();
+
+        let mut arms = Vec::new();
+        for case in cases {
+            let variant = LitStr::new(&case.variant.ident.to_string(), case.variant.ident.span());
+            // An ambiguous nested query is passed on as it is
+            if let Some(forward) = self.gen_forward(case)? {
+                let (call, wrap) = (&forward.call, &forward.wrap);
+                arms.push(forward.gen_if_let(match_expr, quote! {
+                    match #call {
+                        Ok(Some(value)) => {
+                            variants.push(#variant);
+                            if result.is_none() {
+                                result = Some(#wrap);
+                            }
+                        },
+                        Ok(None) => {},
+                        Err(error) => return Err(::core::convert::Into::into(error)),
+                    }
+                }));
+                continue;
+            }
+            let Some(pattern) = self.gen_pattern(case) else { continue; };
+            let Some(output) = self.gen_output(case) else { continue; };
+            let body = self.gen_guarded(case, quote! {
+                variants.push(#variant);
+                if result.is_none() {
+                    result = Some(#output);
+                }
+            });
+            arms.push(quote! {
+                if let #pattern = #match_expr {
+                    #body
+                }
+            });
+        }
+
+        let query = LitStr::new(&self.signature.ident.to_string(), self.signature.ident.span());
+        Ok(quote! {
+            let mut result = None;
+            let mut variants: Vec<&'static str> = Vec::new();
+            #(#arms)*
+            if variants.len() > 1 {
+                return Err(::core::convert::Into::into(#default_error::Ambiguous {
+                    query: #query,
+                    inputs: format!("{:?}", #match_expr),
+                    variants,
+                }));
+            }
+            Ok(result)
+        })
+    }
+
+    pub(crate) fn gen_body_update(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        let assignment = self.gen_assignment(cases, &quote! { &mut value });
+        Ok(quote! {
+            let mut value = self;
+            #assignment;
+            value
+        })
+    }
+
+    pub(crate) fn gen_body_set(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        Ok(self.gen_assignment(cases, &quote! { self }))
+    }
+
+    // Writes the argument named after the binding into whichever field each variant stores it in
+    pub(crate) fn gen_assignment(&self, cases: &Vec<Case>, target: &TokenStream) -> TokenStream {
+        let name = &self.output_names()[0];
+
+        let mut arms = Vec::new();
+        let mut seen_variants = Vec::new();
+        for case in cases {
+            if seen_variants.contains(&&case.variant.ident) { continue; }
+            let Some(member) = case.binding_member(name) else { continue; };
+            seen_variants.push(&case.variant.ident);
+
+            let variant = &case.variant.ident;
+            arms.push(quote! {
+                Self::#variant { #member: slot, .. } => {
+                    *slot = #name;
+                    true
+                }
+            });
+        }
+
+        quote! {
+            match #target {
+                #(#arms,)*
+                _ => false,
+            }
+        }
+    }
+
+    pub(crate) fn gen_body_vec(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        let match_expr = &self.gen_match_expr();
+
+        let mut arms = Vec::new();
+        for case in cases {
+            if let Some(forward) = self.gen_forward(case)? {
+                let (call, wrap) = (&forward.call, &forward.wrap);
+                arms.push(forward.gen_if_let(match_expr, quote! {
+                    result.extend(::core::iter::Iterator::map(::core::iter::IntoIterator::into_iter(#call), |value| #wrap));
+                }));
+                continue;
+            }
+            let Some(pattern) = self.gen_pattern(case) else { continue; };
+            let Some(output) = self.gen_output(case) else { continue; };
+            let body = self.gen_guarded(case, quote! { result.push(#output); });
+            arms.push(quote! {
+                if let #pattern = #match_expr {
+                    #body
+                }
+            });
+        }
+
+        Ok(quote! {
+            let mut result = Vec::new();
+            #(#arms)*
+            result
+        })
+    }
+
+    pub(crate) fn gen_body_iter(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        // The closure is called once per case, so constructors match on references to captured arguments that are
+        // not `Copy`, and clone only the values that the guard and output go on to use
+        let borrowed: Vec<_> = if self.signature.receiver().is_some() { Vec::new() } else {
+            self.signature.inputs.iter()
+                .filter(|input| !matches!(input, FnArg::Typed(arg) if is_copy_type(&arg.ty)))
+                .map(fn_arg_to_ident)
+                .collect()
+        };
+        let match_expr = match self.signature.receiver() {
+            Some(_) => self.gen_match_expr(),
+            None => {
+                let args = self.signature.inputs.iter().map(fn_arg_to_ident)
+                    .map(|name| if borrowed.contains(&name) { quote! { &#name } } else { quote! { #name } });
+                quote! { (#(#args),*) }
+            },
+        };
+
+        let mut arms = Vec::new();
+        for (i, case) in cases.iter().enumerate() {
+            let Some(pattern) = self.gen_pattern(case) else { continue; };
+            let Some(output) = self.gen_output(case) else { continue; };
+            let guard = self.gen_guard(case);
+            let uses = quote! { #output #guard };
+            let clones = self.bound_names(case).into_iter()
+                .filter(|(arg, name)| borrowed.contains(arg) && mentions_ident(uses.clone(), name))
+                .map(|(_, name)| quote! { let #name = ::core::clone::Clone::clone(#name); });
+            let output = match guard {
+                Some(guard) => quote! { if #guard { Some(#output) } else { None } },
+                None => quote! { Some(#output) },
+            };
+            arms.push(quote! {
+                #i => if let #pattern = #match_expr { #(#clones)* #output } else { None }
+            });
+        }
+        let case_count = cases.len();
+
+        Ok(quote! {
+            (0usize..#case_count).filter_map(move |case| match case {
+                #(#arms,)*
+                _ => None,
+            })
+        })
+    }
+
+    pub(crate) fn gen_body_collect(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        let iter = self.gen_body_iter(cases)?;
+        Ok(quote! {
+            ::core::iter::Iterator::collect(#iter)
+        })
+    }
+
+    pub(crate) fn gen_body_bool(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        // A condition naming one of the arguments compares against it, anything else is a pattern
+        let argument = match &self.condition {
+            Some(syn::Expr::Path(path)) => path.path.get_ident().filter(|ident| {
+                self.signature.inputs.iter()
+                    .filter(|input| matches!(input, FnArg::Typed(_)))
+                    .any(|input| fn_arg_to_ident(input) == *ident)
+            }),
+            _ => None,
+        };
+
+        let mut arms = Vec::new();
+        for case in cases {
+            if let Some(forward) = self.gen_forward(case)? {
+                let (pattern, call) = (forward.pattern(), &forward.call);
+                arms.push(quote! { #pattern => #call });
+                continue;
+            }
+            let Some(pattern) = self.gen_pattern(case) else { continue; };
+            if let [name] = self.output_names() && let Some(Binding::Pattern { pat, .. }) = case.bindings.get(name) {
+                let output = match (&self.condition, argument) {
+                    (_, Some(argument)) => quote! { matches!(#argument, #pat) },
+                    (Some(condition), None) => quote! { matches!(#condition, #pat) },
+                    (None, None) => quote! { true },
+                };
+                arms.push(quote! { #pattern => #output });
+                continue;
+            }
+            let Some(value) = self.gen_binding_output(case) else { continue; };
+            // Fields are matched by reference on `&self`, so compare the values behind them
+            let value = match self.output_names() {
+                [name] if self.receiver_kind().is_some_and(|receiver| receiver != ReceiverKind::Value)
+                    && case.binding_field(name).is_some() => quote! { *#value },
+                _ => value,
+            };
+            let output = match (&self.condition, argument) {
+                (_, Some(argument)) => quote! { #value == #argument },
+                (Some(condition), None) => quote! { matches!(#value, #condition) },
+                (None, None) => quote! { true },
+            };
+            arms.push(quote! { #pattern => #output });
+        }
+        arms.push(quote! { _ => false });
+
+        let match_expr = &self.gen_match_expr();
+        Ok(quote! {
+            match #match_expr {
+                #(#arms),*
+            }
+        })
+    }
+
+    pub(crate) fn gen_body_any(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        let match_expr = &self.gen_match_expr();
+
+        let mut arms = Vec::new();
+        for case in cases {
+            if let Some(forward) = self.gen_forward(case)? {
+                let call = &forward.call;
+                arms.push(quote! { if #call { return true; } });
+                continue;
+            }
+            let Some(pattern) = self.gen_pattern(case) else { continue; };
+            let body = self.gen_guarded(case, quote! { return true; });
+            arms.push(quote! {
+                if let #pattern = #match_expr {
+                    #body
+                }
+            });
+        }
+
+        Ok(quote! {
+            #(#arms)*
+            false
+        })
+    }
+
+    pub(crate) fn gen_body_count(&self, cases: &Vec<Case>) -> Result<TokenStream> {
+        let match_expr = &self.gen_match_expr();
+
+        let mut arms = Vec::new();
+        for case in cases {
+            if let Some(forward) = self.gen_forward(case)? {
+                let call = &forward.call;
+                arms.push(quote! { count += #call; });
+                continue;
+            }
+            let Some(pattern) = self.gen_pattern(case) else { continue; };
+            let body = self.gen_guarded(case, quote! { count += 1; });
+            arms.push(quote! {
+                if let #pattern = #match_expr {
+                    #body
+                }
+            });
+        }
+
+        Ok(quote! {
+            let mut count: usize = 0;
+            #(#arms)*
+            count
+        })
+    }
+
+    /// Fills in argument and return types written as `_` from the types of the bindings involved
+    pub fn infer_types(&mut self, cases: &Vec<Case>, schema: &SchemaSpec, default_error: &Ident) -> Result<()> {
+        let returns_binding = self.returns_binding();
+        for input in &mut self.signature.inputs {
+            let FnArg::Typed(pat_type) = input else { continue; };
+            let Type::Infer(_) = pat_type.ty.as_ref() else { continue; };
+            let Pat::Ident(PatIdent { ident, .. }) = pat_type.pat.as_ref() else { continue; };
+            let ty = binding_type(cases, schema, ident)?;
+            // Constructors storing the argument in a field need it to live as long as the field type says
+            let is_stored = !returns_binding && cases.iter().any(|case| case.binding_field(ident).is_some());
+            *pat_type.ty = if is_stored { ty } else { argument_type(&ty) };
+        }
+
+        let ReturnType::Type(_, ty) = &self.signature.output else { return Ok(()); };
+        let Type::Infer(_) = ty.as_ref() else { return Ok(()); };
+        let value: Type = if self.returns_binding() {
+            // Fields that cannot be copied out of `&self` are returned by reference
+            let borrows = self.receiver_kind() == Some(ReceiverKind::Ref);
+            let types = self.output_names().iter()
+                .map(|name| {
+                    let ty = binding_type(cases, schema, name)?;
+                    let is_field = cases.iter().any(|case| case.binding_field(name).is_some());
+                    Ok(if borrows && is_field && !is_copy_type(&ty) { parse_quote! { &#ty } } else { ty })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            match types.as_slice() {
+                [ty] => ty.clone(),
+                types => parse_quote! { (#(#types),*) },
+            }
+        } else {
+            parse_quote! { Self }
+        };
+        let error: Type = match &self.error_type {
+            Some(error_type) => error_type.clone(),
+            None => parse_quote! { #default_error },
+        };
+        let output: Type = { use OutputMode::*; match self.output_mode {
+            Option => parse_quote! { Option<#value> },
+            Strict | Unwrap => value,
+            Vec => parse_quote! { Vec<#value> },
+            Result => parse_quote! { Result<#value, #error> },
+            Single => parse_quote! { Result<Option<#value>, #error> },
+            Iter => parse_quote! { impl Iterator<Item = #value> },
+            Bool | Any | Set => parse_quote! { bool },
+            Count => parse_quote! { usize },
+            Update => parse_quote! { Self },
+            Collect => {
+                let function = &self.signature.ident;
+                return Err(Error::new_spanned(function,
+                    format!(r#"Cannot infer the collection function "{function}" returns, write out its full signature"#)));
+            },
+        }};
+        self.signature.output = parse_quote! { -> #output };
+        Ok(())
+    }
+
+    /// Checks the signature against the output mode and the bindings of `cases`
+    pub fn validate(&self, cases: &Vec<Case>) -> Result<()> {
+        let function = &self.signature.ident;
+
+        if self.returns_binding() {
+            for name in self.output_names() {
+                if !cases.iter().any(|r| r.has_binding(name)) {
+                    return Err(Error::new_spanned(name,
+                        format!(r#"Function "{function}" must return binding "{name}", but no variant has that binding"#)));
+                }
+            }
+        }
+
+        if self.returns_binding() && self.output_mode != OutputMode::Bool {
+            for name in self.output_names() {
+                if let Some(case) = cases.iter().find(|case| case.is_pattern_only(name)) {
+                    let variant = &case.variant.ident;
+                    let kind = if matches!(case.bindings.get(name), Some(Binding::Pattern { .. })) { "a pattern" } else { "guarded" };
+                    return Err(Error::new_spanned(name,
+                        format!(r#"Binding "{name}" is {kind} in variant "{variant}", so function "{function}" cannot return it"#)));
+                }
+            }
+        }
+
+        if self.signature.receiver().is_some() {
+            for name in self.output_names() {
+                if self.output_mode == OutputMode::Strict
+                && let Some(case) = cases.iter().find(|r| !r.has_binding(name)) {
+                    let variant = &case.variant.ident;
+                    return Err(Error::new_spanned(name,
+                        format!(r#"Cannot determine what function "{function}" should return for variant "{variant}""#)));
+                }
+            }
+        }
+
+        if matches!(self.output_mode, OutputMode::Iter | OutputMode::Collect | OutputMode::Update | OutputMode::Set)
+        && let Some(case) = cases.iter().find(|case| case.flatten().is_some()) {
+            let variant = &case.variant.ident;
+            return Err(Error::new_spanned(function,
+                format!(r#"Function "{function}" cannot be forwarded to the nested enum in variant "{variant}""#)));
+        }
+
+        if self.optional_args && self.signature.receiver().is_some() {
+            return Err(Error::new_spanned(function,
+                format!(r#"Function "{function}" takes "self", so it cannot have optional arguments"#)));
+        }
+
+        if self.signature.receiver().is_none() && !self.optional_args {
+            for input in &self.signature.inputs {
+                let name = fn_arg_to_ident(input);
+                if let Some(case) = cases.iter().find(|r| !r.has_binding(name)) {
+                    let variant = &case.variant.ident;
+                    return Err(Error::new_spanned(name,
+                        format!(r#"Variant "{variant}" does not have binding "{name}", \
+                                   cannot determine when function "{function}" should return it"#)));
+                }
+            }
+        }
+
+        if self.signature.receiver().is_some() {
+            for input in &self.signature.inputs {
+                let FnArg::Typed(pat_type) = input else { continue; };
+                let Pat::Ident(PatIdent { ident, .. }) = pat_type.pat.as_ref() else {
+                    panic!("Expected a simple function argument");
+                };
+                if self.is_assignment() && self.output_names().contains(ident) { continue; }
+                for case in cases {
+                    match case.bindings.get(ident) {
+                        None | Some(Binding::Field { .. }) => continue,
+                        _ => {
+                            let variant = &case.variant.ident;
+                            return Err(Error::new_spanned(ident,
+                                format!(r#"Argument "{ident}" in function "{function}"
+                                           conflicts with a binding of the same name in variant "{variant}""#)));
+                        },
+                    }
+                }
+            }
+        }
+
+        match (&self.output_mode, self.signature.receiver()) {
+            (OutputMode::Bool, None) => {
+                return Err(Error::new_spanned(&self.signature,
+                    format!(r#"Function "{function}" must take "self" to return 'Bool'; use 'Any' for functions without "self""#)));
+            },
+            (OutputMode::Any | OutputMode::Count, Some(receiver)) => {
+                return Err(Error::new_spanned(receiver,
+                    format!(r#"Function "{function}" cannot take "self" to return 'Any' or 'Count'; use 'Bool' instead"#)));
+            },
+            _ => {},
+        }
+
+        if let Some(condition) = &self.condition
+        && self.output_mode != OutputMode::Bool {
+            return Err(Error::new_spanned(condition, "Only 'Bool' queries can have a condition"));
+        }
+
+        if let Some(error_type) = &self.error_type
+        && !matches!(self.output_mode, OutputMode::Result | OutputMode::Single) {
+            return Err(Error::new_spanned(error_type, "Only 'Result' and 'Single' queries can have an error type"));
+        }
+
+        if self.output_mode == OutputMode::Single {
+            self.validate_single(cases)?;
+        }
+
+        if self.is_assignment() {
+            self.validate_assignment()?;
+        }
+
+        if self.returns_self_on_error()
+        && (self.output_mode != OutputMode::Result || self.receiver_kind() != Some(ReceiverKind::Value)) {
+            return Err(Error::new_spanned(&self.error_type,
+                format!(r#"Function "{function}" can only return "Self" as an error from 'Result' when it takes "self" by value"#)));
+        }
+
+        if self.receiver_kind() == Some(ReceiverKind::RefMut) && self.borrows_fields() {
+            for name in self.output_names() {
+                for case in cases {
+                    if case.gen_binding_value(name).is_some() && case.binding_field(name).is_none() {
+                        let variant = &case.variant.ident;
+                        return Err(Error::new_spanned(name,
+                            format!(r#"Binding "{name}" of variant "{variant}" is not stored in a field, so function "{function}" cannot borrow it mutably"#)));
+                    }
+                }
+            }
+        }
+
+        if let ReturnType::Default = &self.signature.output {
+            return Err(Error::new_spanned(&self.signature, "Function must have a return type"));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn validate_assignment(&self) -> Result<()> {
+        let function = &self.signature.ident;
+        let (mode, receiver) = match self.output_mode {
+            OutputMode::Update => ("Update", ReceiverKind::Value),
+            _ => ("Set", ReceiverKind::RefMut),
+        };
+        if self.receiver_kind() != Some(receiver) {
+            let expected = if receiver == ReceiverKind::Value { "self" } else { "&mut self" };
+            return Err(Error::new_spanned(&self.signature,
+                format!(r#"Function "{function}" must take "{expected}" to return '{mode}'"#)));
+        }
+        let [name] = self.output_names() else {
+            return Err(Error::new_spanned(&self.signature, format!("'{mode}' can only assign a single binding")));
+        };
+        let has_argument = self.signature.inputs.iter()
+            .any(|input| matches!(input, FnArg::Typed(_)) && fn_arg_to_ident(input) == name);
+        if !has_argument {
+            return Err(Error::new_spanned(&self.signature.inputs,
+                format!(r#"Function "{function}" must take the new value of binding "{name}" as an argument named "{name}""#)));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn is_assignment(&self) -> bool {
+        matches!(self.output_mode, OutputMode::Update | OutputMode::Set)
+    }
+
+    pub(crate) fn validate_single(&self, cases: &Vec<Case>) -> Result<()> {
+        let function = &self.signature.ident;
+        for (i, first) in cases.iter().enumerate() {
+            for second in &cases[i + 1..] {
+                let overlaps = if self.signature.receiver().is_some() {
+                    first.variant.ident == second.variant.ident
+                    && self.gen_binding_output(first).is_some()
+                    && self.gen_binding_output(second).is_some()
+                } else {
+                    self.signature.inputs.iter().map(fn_arg_to_ident).all(|name| {
+                        match (first.bindings.get(name), second.bindings.get(name)) {
+                            (Some(first), Some(second)) => first.overlaps(second),
+                            _ => false,
+                        }
+                    })
+                };
+                if overlaps && self.signature.receiver().is_some() {
+                    let (first, second) = (&first.variant.ident, &second.variant.ident);
+                    return Err(Error::new_spanned(second,
+                        format!(r#"Variants "{first}" and "{second}" always match the same inputs, so function "{function}" would be ambiguous"#)));
+                }
+                if overlaps {
+                    // Name the shared value of each argument: the literal one side requires, or any value if neither does
+                    let shared: Vec<_> = self.signature.inputs.iter().map(fn_arg_to_ident).map(|name| {
+                        let value = [first, second].iter()
+                            .filter_map(|case| match case.bindings.get(name) {
+                                Some(binding @ Binding::Expr { expr, .. }) if !binding.is_irrefutable() => Some(expr.to_token_stream().to_string()),
+                                _ => None,
+                            })
+                            .next()
+                            .unwrap_or_else(|| "any value".to_string());
+                        format!("{name} = {value}")
+                    }).collect();
+                    let (first, second) = (&first.variant.ident, &second.variant.ident);
+                    return Err(Error::new_spanned(second,
+                        format!(r#"Variants "{first}" and "{second}" both match {}, so function "{function}" would be ambiguous"#, shared.join(", "))));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn gen_match_expr(&self) -> TokenStream {
+        if let Some(receiver) = self.receiver_kind() {
+            if receiver != ReceiverKind::Value && !self.matches_by_reference() {
+                quote! { *self }
+            } else {
+                quote! { self }
+            }
+        } else {
+            let arg_names: Vec<_> = self.signature.inputs.iter().map(fn_arg_to_ident).collect();
+            quote! { (#(#arg_names),*) }
+        }
+    }
+
+    // Accessors returning borrowed data match on `self` by reference instead of copying out of `*self`
+    pub(crate) fn borrows_fields(&self) -> bool {
+        let Some(receiver) = self.receiver_kind() else { return false; };
+        receiver != ReceiverKind::Value && self.output_type().is_some_and(is_borrowed_type)
+    }
+
+    // Predicates only look at fields, so like borrowing accessors they never need to copy them out
+    pub(crate) fn matches_by_reference(&self) -> bool {
+        self.borrows_fields() || (self.receiver_kind().is_some() && self.output_mode == OutputMode::Bool)
+    }
+
+    // `Result(binding, Self)` hands the value back to the caller when the binding is missing
+    pub(crate) fn returns_self_on_error(&self) -> bool {
+        matches!(&self.error_type, Some(Type::Path(path)) if path.path.is_ident("Self"))
+    }
+
+    pub(crate) fn receiver_kind(&self) -> Option<ReceiverKind> {
+        let receiver = self.signature.receiver()?;
+        match receiver.ty.as_ref() {
+            Type::Reference(reference) if reference.mutability.is_some() => Some(ReceiverKind::RefMut),
+            Type::Reference(_) => Some(ReceiverKind::Ref),
+            _ => Some(ReceiverKind::Value),
+        }
+    }
+
+    pub(crate) fn output_type(&self) -> Option<&Type> {
+        let ReturnType::Type(_, ty) = &self.signature.output else { return None; };
+        use OutputMode::*;
+        match self.output_mode {
+            Strict | Unwrap => Some(ty),
+            Option | Vec | Collect | Result => type_argument(ty, 0),
+            Single => type_argument(type_argument(ty, 0)?, 0),
+            Iter => iterator_item_type(ty),
+            Bool | Any | Count | Update | Set => None,
+        }
+    }
+
+    pub(crate) fn gen_pattern(&self, case: &Case) -> Option<TokenStream> {
+        // Flattened variants are only reached by forwarding
+        if case.flatten().is_some() {
+            return None;
+        }
+        if self.signature.receiver().is_some() {
+            let condition = &self.condition;
+            let mut uses = quote! { #condition };
+            for name in self.output_names() {
+                uses.extend(case.gen_binding_value(name));
+            }
+            return Some(case.gen_self_pattern(&uses));
+        }
+
+        let mut patterns = Vec::new();
+
+        for input in &self.signature.inputs {
+            let name = fn_arg_to_ident(input);
+            use Binding::*;
+            // With `optional_args`, `None` matches every case and `Some(value)` matches as usual
+            let pattern = if self.optional_args { match case.bindings.get(name) {
+                Some(Field { name }) => quote! { Some(#name) },
+                Some(Expr { expr: syn::Expr::Infer(_), .. }) => quote! { _ },
+                Some(Expr { expr, .. }) if case.mentions_field(expr) && plain_ident(expr).is_some() => quote! { Some(#expr) },
+                Some(Expr { name, expr, .. }) if case.mentions_field(expr) => quote! { Some(#name @ #expr) },
+                // Guards see the bound value, so an argument of `None` is checked in the guard instead
+                Some(Expr { name, guard: Some(_), .. }) => quote! { #name },
+                Some(Expr { expr, .. }) => quote! { None | Some(#expr) },
+                Some(Pattern { pat, .. }) => quote! { None | Some(#pat) },
+                Some(Flatten { .. }) | Some(Never { .. }) | None => quote! { None },
+            }} else { match case.bindings.get(name) {
+                Some(Field { name }) => quote! { #name },
+                Some(Expr { name, expr: syn::Expr::Infer(_), .. }) => quote! { #name },
+                // A plain identifier already binds the argument, and binding it again as `name @` would move it twice
+                Some(Expr { expr, .. }) if plain_ident(expr).is_some() => quote! { #expr },
+                Some(Expr { name, expr, .. }) => quote! { #name @ #expr },
+                Some(Pattern { pat, .. }) => quote! { #pat },
+                Some(Flatten { .. }) | Some(Never { .. }) => return None,
+                None => quote! { #name },
+            }};
+            patterns.push(pattern);
+        }
+        Some(quote! { (#(#patterns),*) })
+    }
+
+    // Names a constructor pattern binds to the arguments, besides any inside user patterns
+    pub(crate) fn bound_names<'a>(&'a self, case: &Case) -> Vec<(&'a Ident, Ident)> {
+        let mut names = Vec::new();
+        if self.signature.receiver().is_some() {
+            return names;
+        }
+        for input in &self.signature.inputs {
+            let arg = fn_arg_to_ident(input);
+            use Binding::*;
+            match case.bindings.get(arg) {
+                Some(Field { name }) => names.push((arg, name.clone())),
+                Some(Expr { expr: syn::Expr::Infer(_), .. }) if self.optional_args => {},
+                Some(Expr { name, expr, guard }) if !self.optional_args || case.mentions_field(expr) || guard.is_some() => {
+                    let bound = match plain_ident(expr) {
+                        Some(ident) if !self.optional_args || case.mentions_field(expr) => ident,
+                        _ => name,
+                    };
+                    names.push((arg, bound.clone()));
+                },
+                Some(Expr { .. }) | Some(Pattern { .. }) | Some(Flatten { .. }) | Some(Never { .. }) => {},
+                None if self.optional_args => {},
+                None => names.push((arg, arg.clone())),
+            }
+        }
+        names
+    }
+
+    // Guards of the bindings a constructor query matches on; when one fails, later cases are tried
+    pub(crate) fn gen_guard(&self, case: &Case) -> Option<TokenStream> {
+        if self.signature.receiver().is_some() {
+            return None;
+        }
+
+        let mut guards = Vec::new();
+        for input in &self.signature.inputs {
+            let name = fn_arg_to_ident(input);
+            use Binding::*;
+            let guard = match case.bindings.get(name) {
+                Some(Expr { expr, guard: Some(guard), .. }) if !self.optional_args || case.mentions_field(expr) => quote! { #guard },
+                Some(Pattern { guard: Some(guard), .. }) if !self.optional_args => quote! { #guard },
+                Some(Expr { name, expr, guard: Some(guard) }) => quote! {
+                    match #name { Some(#expr) => #guard, None => true, _ => false }
+                },
+                Some(Pattern { name, guard: Some(guard), .. }) => quote! {
+                    match #name { Some(#name) => #guard, None => true }
+                },
+                _ => continue,
+            };
+            guards.push(guard);
+        }
+        join_guards(guards)
+    }
+
+    // Cases with `#[bind(flatten = field)]` forward to the query of the same name on the nested enum
+    pub(crate) fn gen_forward(&self, case: &Case) -> Result<Option<Forward>> {
+        let Some(field) = case.flatten() else { return Ok(None); };
+        let Some(member) = case.field_member(field) else { return Ok(None); };
+        let function = &self.signature.ident;
+        let arguments: Vec<_> = self.signature.inputs.iter()
+            .filter(|input| matches!(input, FnArg::Typed(_)))
+            .map(fn_arg_to_ident)
+            .collect();
+        let variant = &case.variant.ident;
+
+        let ty = &case.field(field).expect("flattened field exists").ty;
+        let boxed = match ty {
+            Type::Path(path) if path.path.segments.last().is_some_and(|segment| segment.ident == "Box") => type_argument(ty, 0),
+            _ => None,
+        };
+        let value = match boxed {
+            Some(_) => quote! { ::std::boxed::Box::new(value) },
+            None => quote! { value },
+        };
+        // The variant can only be built around the nested value if it is the only field
+        let rebuild = (case.variant.fields.len() == 1).then(|| quote! { Self::#variant { #member: #value } });
+
+        if let Some(receiver) = self.receiver_kind() {
+            if self.returns_self_on_error() && rebuild.is_none() {
+                return Err(Error::new_spanned(field,
+                    format!(r#"Variant "{variant}" has fields besides "{field}", so function "{function}" cannot hand it back as an error"#)));
+            }
+            let binding_mode = match (receiver, self.matches_by_reference()) {
+                (ReceiverKind::Ref, false) => quote! { ref },
+                (ReceiverKind::RefMut, false) => quote! { ref mut },
+                _ => quote! {},
+            };
+            return Ok(Some(Forward {
+                pattern: Some(quote! { Self::#variant { #member: #binding_mode inner, .. } }),
+                call: quote! { inner.#function(#(#arguments),*) },
+                wrap: quote! { value },
+                rebuild,
+            }));
+        }
+
+        // A recursive variant would look itself up with the same arguments forever, so constructors skip it,
+        // and values nested inside it are never found
+        let inner_type = boxed.unwrap_or(ty);
+        if matches!(inner_type, Type::Path(path) if path.path.is_ident("Self")) { return Ok(None); }
+
+        let wrap = if self.returns_binding() || matches!(self.output_mode, OutputMode::Any | OutputMode::Count) {
+            quote! { value }
+        } else if let Some(rebuild) = &rebuild {
+            rebuild.clone()
+        } else {
+            return Err(Error::new_spanned(field,
+                format!(r#"Variant "{variant}" has fields besides "{field}", so function "{function}" cannot build it"#)));
+        };
+        Ok(Some(Forward {
+            pattern: None,
+            call: quote! { <#inner_type>::#function(#(#arguments),*) },
+            wrap,
+            rebuild,
+        }))
+    }
+
+    pub(crate) fn gen_arm_pattern(&self, case: &Case) -> Option<TokenStream> {
+        let pattern = self.gen_pattern(case)?;
+        match self.gen_guard(case) {
+            Some(guard) => Some(quote! { #pattern if #guard }),
+            None => Some(pattern),
+        }
+    }
+
+    pub(crate) fn gen_guarded(&self, case: &Case, body: TokenStream) -> TokenStream {
+        match self.gen_guard(case) {
+            Some(guard) => quote! { if #guard { #body } },
+            None => body,
+        }
+    }
+
+    pub(crate) fn gen_output(&self, case: &Case) -> Option<TokenStream> {
+        let output = if self.returns_binding() {
+            self.gen_binding_output(case)
+        } else {
+            Some(case.gen_self_expr())
+        };
+
+        use OutputMode::*;
+        
+        match (output, &self.output_mode) {
+            (Some(output), Option)                                          => Some(quote! { Some( #output ) }),
+            (Some(output), Result)                                          => Some(quote! { Ok( #output ) }),
+            (Some(output), Strict | Unwrap | Vec | Iter | Collect | Single) => Some(output),
+            (None,         Option)                                          => Some(quote! { None }),
+            (None,         Unwrap)                                          => Some(quote! { panic!("Cannot determine what to return for this variant") }),
+            (None,         Strict | Vec | Result | Iter | Collect | Single) => None,
+            (_,            Bool | Any | Count | Update | Set)               => None,
+        }
+    }
+
+    pub(crate) fn gen_binding_output(&self, case: &Case) -> Option<TokenStream> {
+        let mut values = Vec::new();
+        for name in self.output_names() {
+            values.push(case.gen_binding_value(name)?);
+        }
+        match values.as_slice() {
+            [value] => Some(value.clone()),
+            values => Some(quote! { (#(#values),*) }),
+        }
+    }
+
+    // Functions without `self` return the matching variant, unless they name bindings to project instead
+    pub(crate) fn returns_binding(&self) -> bool {
+        self.signature.receiver().is_some() || !self.output_names.is_empty()
+    }
+
+    /// The bindings named in `return = Mode(...)`, or else the binding named after the function
+    pub fn output_names(&self) -> &[Ident] {
+        if self.output_names.is_empty() {
+            std::slice::from_ref(&self.signature.ident)
+        } else {
+            &self.output_names
+        }
+    }
+}
+
+impl Parse for FunctionSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ident = input.parse::<Ident>()?;
+        if ident != "query" { return Err(Error::new_spanned(ident, "Expected 'query'")); }
+        let input = { let content; parenthesized!(content in input); content };
+        Self::parse_body(&input)
+    }
+}
+
+impl FunctionSpec {
+    // Everything inside `query(...)`, which may also be one of several `;`-separated specs in a trait impl
+    pub(crate) fn parse_body(input: ParseStream) -> Result<Self> {
+        let visibility = input.parse::<Visibility>()?;
+        let signature = if input.peek(Token![fn]) {
+            input.parse::<Signature>()?
+        } else {
+            // `name` is short for `fn name(&self) -> _`, and `by name` for `fn by_name(name: _) -> _`
+            let ident = input.parse::<Ident>()?;
+            if ident == "by" && input.peek(syn::Ident) {
+                let name = input.parse::<Ident>()?;
+                let ident = format_ident!("by_{name}", span = name.span());
+                parse_quote! { fn #ident(#name: _) -> _ }
+            } else {
+                parse_quote! { fn #ident(&self) -> _ }
+            }
+        };
+        let mut output_mode = OutputMode::Option;
+        let mut output_names: Vec<Ident> = Vec::new();
+        let mut condition: Option<Expr> = None;
+        let mut error_type: Option<Type> = None;
+        let mut optional_args = false;
+        while !input.is_empty() && !input.peek(Token![;]) {
+            input.parse::<Token![,]>()?;
+            if !input.peek(Token![return]) {
+                let flag = input.parse::<Ident>()?;
+                if flag != "optional_args" { return Err(Error::new_spanned(flag, "Expected 'return' or 'optional_args'")); }
+                optional_args = true;
+                continue;
+            }
+            input.parse::<Token![return]>()?;
+            input.parse::<Token![=]>()?;
+            output_mode = input.parse::<OutputMode>()?;
+            if input.peek(syn::token::Paren) {
+                let input = { let content; parenthesized!(content in input); content };
+                let has_error_type = matches!(output_mode, OutputMode::Result | OutputMode::Single);
+                if input.peek(Token![_]) {
+                    input.parse::<Token![_]>()?;
+                } else if input.peek(syn::token::Paren) {
+                    let input = { let content; parenthesized!(content in input); content };
+                    output_names.extend(Punctuated::<Ident, Token![,]>::parse_terminated(&input)?);
+                } else {
+                    output_names.push(input.parse::<Ident>()?);
+                }
+                if input.peek(Token![=]) {
+                    input.parse::<Token![=]>()?;
+                    condition = Some(input.parse::<Expr>()?);
+                }
+                if has_error_type && !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                    let ty = input.parse::<Type>()?;
+                    // Types are capitalized, so a lowercase name is most likely a second binding
+                    if let Type::Path(path) = &ty && let Some(ident) = path.path.get_ident()
+                        && ident.to_string().starts_with(char::is_lowercase) {
+                        return Err(Error::new_spanned(&ty, format!(
+                            "{output_mode:?}(a, {ident}) takes \"{ident}\" as the error type; write {output_mode:?}((a, {ident})) to return several bindings")));
+                    }
+                    error_type = Some(ty);
+                }
+                while !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                    output_names.push(input.parse::<Ident>()?);
+                }
+            }
+        }
+
+        Ok(FunctionSpec { visibility, signature, output_mode, output_names, condition, error_type, optional_args })
+    }
+}
+
+// How a query reaches into a flattened variant: receivers match the nested value, constructors call its lookup
+pub(crate) struct Forward {
+    pub(crate) pattern: Option<TokenStream>,
+    pub(crate) call: TokenStream,
+    // Turns the nested query's `value` into this query's output
+    pub(crate) wrap: TokenStream,
+    // Builds the variant back around `value`, if the nested value is its only field
+    pub(crate) rebuild: Option<TokenStream>,
+}
+
+impl Forward {
+    pub(crate) fn pattern(&self) -> TokenStream {
+        match &self.pattern {
+            Some(pattern) => pattern.clone(),
+            None => quote! { _ },
+        }
+    }
+
+    // Queries returning a value in every case forward it directly
+    pub(crate) fn gen_value_arm(&self, _rest: Option<TokenStream>) -> TokenStream {
+        let (pattern, call, wrap) = (self.pattern(), &self.call, &self.wrap);
+        match self.pattern {
+            Some(_) => quote! { #pattern => #call },
+            None => quote! { _ => { let value = #call; #wrap } },
+        }
+    }
+
+    pub(crate) fn gen_if_let(&self, match_expr: &TokenStream, body: TokenStream) -> TokenStream {
+        match &self.pattern {
+            Some(pattern) => quote! { if let #pattern = #match_expr { #body } },
+            None => body,
+        }
+    }
+}
+
+pub(crate) fn join_guards(guards: Vec<TokenStream>) -> Option<TokenStream> {
+    match guards.as_slice() {
+        [] => None,
+        [guard] => Some(guard.clone()),
+        guards => Some(quote! { #((#guards))&&* }),
+    }
+}
+
+// A lowercase identifier, which as a pattern binds whatever it is matched against
+pub(crate) fn plain_ident(expr: &Expr) -> Option<&Ident> {
+    let Expr::Path(path) = expr else { return None; };
+    path.path.get_ident().filter(|ident| ident.to_string().starts_with(|c: char| c.is_lowercase() || c == '_'))
+}
+
+pub(crate) fn fn_arg_to_ident(arg: &FnArg) -> &Ident {
+    let FnArg::Typed(pat_type) = arg else { panic!("Expected a simple function argument"); };
+    let Pat::Ident(pat_ident) = pat_type.pat.as_ref() else { panic!("Expected a simple function argument"); };
+    &pat_ident.ident
+}
@@ -0,0 +1,129 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    Error, GenericArgument, Generics, PathArguments, Result, Token, Type, parse::{Parse, ParseStream}, parse_quote
+};
+
+use crate::{case::Case, function_spec::FunctionSpec};
+
+// Parsed from one entry of `#[bind_impl(...)]`, e.g. `TryFrom<u8> = code`
+pub(crate) enum ImplSpec {
+    FromStr { binding: Ident },
+    Display { binding: Ident },
+    TryFrom { source: Type, binding: Ident },
+    From { source: Ident, target: Type, binding: Ident },
+    PartialEq { other: Type, binding: Ident },
+}
+
+impl ImplSpec {
+    pub(crate) fn binding(&self) -> &Ident {
+        match self {
+            ImplSpec::FromStr { binding } | ImplSpec::Display { binding } => binding,
+            ImplSpec::TryFrom { binding, .. } | ImplSpec::From { binding, .. } | ImplSpec::PartialEq { binding, .. } => binding,
+        }
+    }
+
+    pub(crate) fn accessor_name(&self) -> Ident {
+        format_ident!("__bind_{}", self.binding())
+    }
+
+    // Conversions from `Self` read the binding through a strict accessor, so every variant must have it
+    pub(crate) fn accessor(&self) -> Option<FunctionSpec> {
+        let (binding, accessor_name) = (self.binding(), self.accessor_name());
+        match self {
+            ImplSpec::Display { .. } | ImplSpec::From { .. } | ImplSpec::PartialEq { .. } =>
+                Some(parse_quote! { query(fn #accessor_name(&self) -> _, return = Strict(#binding)) }),
+            ImplSpec::FromStr { .. } | ImplSpec::TryFrom { .. } => None,
+        }
+    }
+
+    pub(crate) fn gen_impl(&self, enum_name: &Ident, generics: &Generics, cases: &Vec<Case>, default_error: &Ident) -> Result<TokenStream> {
+        let accessor_name = self.accessor_name();
+        let generic_params = &generics.params;
+        Ok(match self {
+            ImplSpec::FromStr { binding } => {
+                let spec: FunctionSpec = parse_quote! { query(fn from_str(#binding: &str) -> Result<Self, Self::Err>, return = Result) };
+                spec.validate(cases)?;
+                let function = spec.gen_function(cases, default_error)?;
+                quote! {
+                    impl <#generic_params> ::core::str::FromStr for #enum_name #generics {
+                        type Err = #default_error;
+                        #function
+                    }
+                }
+            },
+            ImplSpec::TryFrom { source, binding } => {
+                let spec: FunctionSpec = parse_quote! { query(fn try_from(#binding: #source) -> Result<Self, Self::Error>, return = Result) };
+                spec.validate(cases)?;
+                let function = spec.gen_function(cases, default_error)?;
+                quote! {
+                    impl <#generic_params> ::core::convert::TryFrom<#source> for #enum_name #generics {
+                        type Error = #default_error;
+                        #function
+                    }
+                }
+            },
+            ImplSpec::Display { .. } => quote! {
+                impl <#generic_params> ::core::fmt::Display for #enum_name #generics {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        ::core::fmt::Display::fmt(&self.#accessor_name(), f)
+                    }
+                }
+            },
+            ImplSpec::From { source, target, .. } => {
+                if source != "Self" && source != enum_name {
+                    return Err(Error::new_spanned(source, format!("Expected From<Self> or From<{enum_name}>")));
+                }
+                quote! {
+                    impl <#generic_params> ::core::convert::From<#enum_name #generics> for #target {
+                        fn from(value: #enum_name #generics) -> Self {
+                            value.#accessor_name()
+                        }
+                    }
+                }
+            },
+            ImplSpec::PartialEq { other, .. } => quote! {
+                impl <#generic_params> ::core::cmp::PartialEq<#other> for #enum_name #generics {
+                    fn eq(&self, other: &#other) -> bool {
+                        ::core::cmp::PartialEq::eq(&self.#accessor_name(), other)
+                    }
+                }
+            },
+        })
+    }
+}
+
+impl Parse for ImplSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let path = input.parse::<syn::Path>()?;
+        let target = if input.peek(Token![for]) {
+            input.parse::<Token![for]>()?;
+            Some(input.parse::<Type>()?)
+        } else {
+            None
+        };
+        input.parse::<Token![=]>()?;
+        let binding = input.parse::<Ident>()?;
+
+        let Some(segment) = path.segments.last() else { return Err(Error::new_spanned(path, "Expected a trait")); };
+        let argument = match &segment.arguments {
+            PathArguments::AngleBracketed(arguments) => arguments.args.iter()
+                .find_map(|argument| match argument { GenericArgument::Type(ty) => Some(ty.clone()), _ => None }),
+            _ => None,
+        };
+        let trait_name = segment.ident.to_string();
+        match (trait_name.as_str(), argument, target) {
+            ("FromStr", None, None) => Ok(ImplSpec::FromStr { binding }),
+            ("Display", None, None) => Ok(ImplSpec::Display { binding }),
+            ("TryFrom", Some(source), None) => Ok(ImplSpec::TryFrom { source, binding }),
+            ("From", Some(source), Some(target)) => match source {
+                Type::Path(path) if path.qself.is_none() && path.path.segments.len() == 1 =>
+                    Ok(ImplSpec::From { source: path.path.segments[0].ident.clone(), target, binding }),
+                _ => Err(Error::new_spanned(source, "Expected From<Self> for T")),
+            },
+            ("PartialEq", Some(other), None) => Ok(ImplSpec::PartialEq { other, binding }),
+            _ => Err(Error::new_spanned(path,
+                "Expected one of FromStr, Display, TryFrom<T>, From<Self> for T or PartialEq<T>")),
+        }
+    }
+}
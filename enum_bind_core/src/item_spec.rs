@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    Attribute, Error, Expr, Lifetime, LitStr, Result, Token, Type, UnOp, Visibility, parse::{Parse, ParseStream}, parse_quote
+};
+
+use crate::{binding::Binding, case::{Case, get_field_name, mentions_ident}, function_spec::{FunctionSpec, OutputMode, join_guards}, types::{argument_type, infer_binding_type}};
+
+// Parsed from `#[bind_row(...)]` and `#[bind_query(...)]`: the name of the generated item, plus types for bindings
+pub(crate) struct ItemSpec {
+    pub(crate) attrs: Vec<Attribute>,
+    pub(crate) name: Ident,
+    pub(crate) types: BTreeMap<Ident, Type>,
+}
+
+impl ItemSpec {
+    pub(crate) fn binding_types(&self, cases: &Vec<Case>, attribute: &str) -> Result<Vec<(Ident, Type)>> {
+        // Names that only ever appear as `name = !` have no value to store
+        let mut names = Vec::new();
+        for case in cases {
+            for (name, binding) in &case.bindings {
+                if !matches!(binding, Binding::Never { .. } | Binding::Flatten { .. }) && !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        names.sort();
+
+        let mut types = Vec::new();
+        for name in names {
+            let ty = match self.types.get(&name) {
+                Some(ty) => ty.clone(),
+                None => infer_binding_type(cases, &name).ok_or_else(|| Error::new_spanned(&name,
+                    format!(r#"Cannot infer the type of binding "{name}", declare it as "{name}: Type" in #[{attribute}(...)]"#)))?,
+            };
+            types.push((name, ty));
+        }
+        Ok(types)
+    }
+
+    pub(crate) fn gen_row(&self, visibility: &Visibility, cases: &Vec<Case>) -> Result<(TokenStream, TokenStream)> {
+        let (names, types): (Vec<_>, Vec<_>) = self.binding_types(cases, "bind_row")?.into_iter().unzip();
+
+        let mut fields = Vec::new();
+        let mut optional = Vec::new();
+        for (name, ty) in names.iter().zip(&types) {
+            let is_optional = cases.iter().any(|case| case.gen_binding_value(name).is_none());
+            let ty = if is_optional { quote! { Option<#ty> } } else { quote! { #ty } };
+            fields.push(quote! { #visibility #name: #ty });
+            optional.push(is_optional);
+        }
+
+        let mut row_arms = Vec::new();
+        let mut seen_variants = Vec::new();
+        for case in cases {
+            if seen_variants.contains(&&case.variant.ident) { continue; }
+            seen_variants.push(&case.variant.ident);
+
+            let mut values = Vec::new();
+            for (name, &is_optional) in names.iter().zip(&optional) {
+                let value = match (case.gen_binding_value(name), is_optional) {
+                    (Some(value), true) => quote! { Some(#value) },
+                    (Some(value), false) => value,
+                    (None, _) => quote! { None },
+                };
+                values.push(quote! { #name: #value });
+            }
+            // Only the fields a binding reads are borrowed, then cloned so the bindings see owned values
+            let uses = quote! { #(#values)* };
+            let pattern = case.gen_self_pattern(&uses);
+            let clones = case.variant.fields.iter().enumerate()
+                .map(|field| get_field_name(&field))
+                .filter(|field| mentions_ident(uses.clone(), field))
+                .map(|field| quote! { let #field = ::core::clone::Clone::clone(#field); });
+            let row_name = &self.name;
+            row_arms.push(quote! { #pattern => { #(#clones)* #row_name { #(#values),* } } });
+        }
+
+        let mut from_row_arms = Vec::new();
+        for case in cases {
+            let mut patterns = Vec::new();
+            let mut comparisons = Vec::new();
+            for (name, &is_optional) in names.iter().zip(&optional) {
+                // Fields are captured by their own bindings, so expressions over them only have to be consistent.
+                // Other expressions are matched directly if they are valid patterns, and compared in the guard if not.
+                let pattern = match case.bindings.get(name) {
+                    Some(Binding::Field { name }) => Some(quote! { #name }),
+                    Some(Binding::Expr { .. }) if case.is_pattern_only(name) => None,
+                    Some(Binding::Expr { expr, .. }) if !case.mentions_field(expr) && is_pattern_expr(expr) => Some(quote! { #expr }),
+                    Some(Binding::Expr { expr, .. }) if !case.mentions_field(expr) => {
+                        comparisons.push(quote! { #name == #expr });
+                        Some(quote! { #name })
+                    },
+                    Some(Binding::Expr { .. }) => Some(quote! { _ }),
+                    Some(Binding::Pattern { name, .. }) => {
+                        let variant = &case.variant.ident;
+                        return Err(Error::new_spanned(name,
+                            format!(r#"Binding "{name}" is a pattern in variant "{variant}", so it cannot be stored in a row"#)));
+                    },
+                    Some(Binding::Flatten { .. }) | Some(Binding::Never { .. }) | None => None,
+                };
+                patterns.push(match (pattern, is_optional) {
+                    (Some(pattern), true) => quote! { Some(#pattern) },
+                    (Some(pattern), false) => pattern,
+                    (None, _) => quote! { None },
+                });
+            }
+            let output = case.gen_self_expr();
+            let guard = join_guards(names.iter()
+                .filter(|name| !case.is_pattern_only(name))
+                .filter_map(|name| case.bindings.get(name)?.guard())
+                .map(|guard| quote! { #guard })
+                .chain(comparisons)
+                .collect()).map(|guard| quote! { if #guard });
+            from_row_arms.push(quote! { (#(#patterns,)*) #guard => Some(#output) });
+        }
+
+        let attrs = &self.attrs;
+        let row_name = &self.name;
+        let item = quote! {
+            #(#attrs)*
+            #visibility struct #row_name {
+                #(#fields),*
+            }
+        };
+        let functions = quote! {
+            #visibility fn row(&self) -> #row_name {
+                match self {
+                    #(#row_arms),*
+                }
+            }
+
+            #[allow(unused_variables, unreachable_patterns)]
+            #visibility fn from_row(row: &#row_name) -> Option<Self> {
+                match (#(::core::clone::Clone::clone(&row.#names),)*) {
+                    #(#from_row_arms,)*
+                    _ => None,
+                }
+            }
+        };
+        Ok((item, functions))
+    }
+
+    pub(crate) fn gen_query(&self, visibility: &Visibility, enum_name: &Ident, cases: &Vec<Case>, default_error: &Ident) -> Result<(TokenStream, TokenStream)> {
+        let (names, types): (Vec<_>, Vec<_>) = self.binding_types(cases, "bind_query")?.into_iter().unzip();
+        let query_name = &self.name;
+
+        // Bindings that are only matched against can be set from borrowed values of any lifetime, not only 'static,
+        // so the builder holds them for a lifetime of its own
+        let lookup_types: Vec<Type> = names.iter().zip(&types).map(|(name, ty)| {
+            let is_stored = cases.iter().any(|case| case.binding_field(name).is_some());
+            if is_stored { ty.clone() } else { argument_type(ty) }
+        }).collect();
+        let lifetime: Lifetime = parse_quote! { 'query };
+        let types: Vec<Type> = lookup_types.iter().map(|ty| match ty {
+            Type::Reference(reference) if reference.lifetime.is_none() => {
+                let mut reference = reference.clone();
+                reference.lifetime = Some(lifetime.clone());
+                Type::Reference(reference)
+            },
+            ty => ty.clone(),
+        }).collect();
+        let lifetimes = (types != lookup_types).then(|| quote! { <#lifetime> });
+
+        // The terminals delegate to hidden lookups with one optional argument per binding
+        let lookups: [(Ident, Type, OutputMode); 3] = [
+            (format_ident!("__bind_query_all"), parse_quote! { Vec<Self> }, OutputMode::Vec),
+            (format_ident!("__bind_query_first"), parse_quote! { Option<Self> }, OutputMode::Option),
+            (format_ident!("__bind_query_single"), parse_quote! { Result<Option<Self>, #default_error> }, OutputMode::Single),
+        ];
+        let [all, first, single] = lookups.each_ref().map(|(ident, _, _)| ident.clone());
+        let mut functions = Vec::new();
+        for (ident, output, output_mode) in lookups {
+            let spec = FunctionSpec {
+                visibility: Visibility::Inherited,
+                signature: parse_quote! { fn #ident(#(#names: Option<#lookup_types>),*) -> #output },
+                output_mode,
+                output_names: Vec::new(),
+                condition: None,
+                error_type: None,
+                optional_args: true,
+            };
+            spec.validate(cases)?;
+            functions.push(spec.gen_function(cases, default_error)?);
+        }
+
+        // Errors name the terminal and the bindings that were set, rather than the lookup behind them
+        let single_name = LitStr::new(&format!("{query_name}::single"), query_name.span());
+        let binding_names: Vec<_> = names.iter().map(|name| LitStr::new(&name.to_string(), name.span())).collect();
+
+        let attrs = &self.attrs;
+        let item = quote! {
+            #(#attrs)*
+            #visibility struct #query_name #lifetimes {
+                #(#names: Option<#types>),*
+            }
+
+            impl #lifetimes #query_name #lifetimes {
+                #(
+                    #visibility fn #names(mut self, #names: #types) -> Self {
+                        self.#names = Some(#names);
+                        self
+                    }
+                )*
+
+                #visibility fn all(self) -> Vec<#enum_name> {
+                    #enum_name::#all(#(self.#names),*)
+                }
+
+                #visibility fn first(self) -> Option<#enum_name> {
+                    #enum_name::#first(#(self.#names),*)
+                }
+
+                #visibility fn single(self) -> Result<Option<#enum_name>, #default_error> {
+                    let mut inputs: Vec<String> = Vec::new();
+                    #(
+                        if let Some(value) = &self.#names {
+                            inputs.push(format!("{} = {:?}", #binding_names, value));
+                        }
+                    )*
+                    #enum_name::#single(#(self.#names),*).map_err(|error| match error {
+                        ::enum_bind::QueryError::NoMatch { .. } =>
+                            ::enum_bind::QueryError::NoMatch { query: #single_name, inputs: inputs.join(", ") },
+                        ::enum_bind::QueryError::Ambiguous { variants, .. } =>
+                            ::enum_bind::QueryError::Ambiguous { query: #single_name, inputs: inputs.join(", "), variants },
+                    })
+                }
+            }
+        };
+        functions.push(quote! {
+            #visibility fn query #lifetimes () -> #query_name #lifetimes {
+                #query_name { #(#names: None),* }
+            }
+        });
+        Ok((item, quote! { #(#functions)* }))
+    }
+}
+
+// Literals, paths and ranges can be pasted into a pattern as they are
+fn is_pattern_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) | Expr::Path(_) => true,
+        Expr::Range(range) => range.start.iter().chain(&range.end).all(|end| is_pattern_expr(end)),
+        Expr::Unary(unary) => matches!(unary.op, UnOp::Neg(_)) && matches!(*unary.expr, Expr::Lit(_)),
+        Expr::Group(group) => is_pattern_expr(&group.expr),
+        _ => false,
+    }
+}
+
+impl Parse for ItemSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let name = input.parse::<Ident>()?;
+        let mut types = BTreeMap::new();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() { break; }
+            let binding = input.parse::<Ident>()?;
+            input.parse::<Token![:]>()?;
+            types.insert(binding, input.parse::<Type>()?);
+        }
+        Ok(ItemSpec { attrs, name, types })
+    }
+}
@@ -0,0 +1,209 @@
+//! Parsing and code generation behind `#[derive(Bind)]`, for other derives that read the same attributes.
+//!
+//! [`get_cases`] (or [`enum_cases`] for a whole enum) turns `#[bind(...)]` attributes into [`Case`]s, one for each
+//! attribute on a variant, mapping binding names to [`Binding`]s. A [`FunctionSpec`] parsed from `query(...)`
+//! generates a query function over those cases, and [`expand`] generates everything `#[derive(Bind)]` does.
+//!
+//! ```
+//! use enum_bind_core::{FunctionSpec, SchemaSpec, enum_cases};
+//! use quote::format_ident;
+//! use syn::parse_quote;
+//!
+//! let ast: syn::DeriveInput = parse_quote! {
+//!     enum Environment {
+//!         #[bind(region = "us")] Prod,
+//!         #[bind(region = "eu")] ProdEu,
+//!     }
+//! };
+//! let cases = enum_cases(&ast).unwrap();
+//! assert_eq!(cases.len(), 2);
+//!
+//! let mut spec: FunctionSpec = parse_quote! { query(pub fn region(&self) -> &'static str, return = Strict) };
+//! let error = format_ident!("EnvironmentQueryError");
+//! spec.infer_types(&cases, &SchemaSpec::default(), &error).unwrap();
+//! spec.validate(&cases).unwrap();
+//! let function = spec.gen_function(&cases, &error).unwrap();
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{ToTokens, format_ident, quote};
+use syn::{Data, Error, Generics, Result, Token, Visibility, parse2, punctuated::Punctuated};
+
+mod binding;
+mod case;
+mod function_spec;
+mod impl_spec;
+mod item_spec;
+mod schema;
+mod serde;
+mod trait_spec;
+mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use binding::Binding;
+pub use case::{Case, get_cases};
+pub use function_spec::{FunctionSpec, OutputMode};
+pub use schema::SchemaSpec;
+
+use impl_spec::ImplSpec;
+use item_spec::ItemSpec;
+use serde::SerdeSpec;
+use trait_spec::TraitSpec;
+
+/// Every variant of the enum, with `#[bind_default(...)]` bindings merged into each of its cases
+pub fn enum_cases(ast: &syn::DeriveInput) -> Result<Vec<Case<'_>>> {
+    let Data::Enum(data_enum) = &ast.data else { return Err(Error::new_spanned(ast, "Expected an enum")); };
+
+    let mut defaults = Vec::new();
+    for attr in &ast.attrs {
+        if !attr.path().is_ident("bind_default") { continue; }
+        defaults.extend(attr.parse_args_with(Punctuated::<Binding, Token![,]>::parse_terminated)?);
+    }
+
+    let mut cases = Vec::new();
+    for variant in &data_enum.variants {
+        cases.extend(get_cases(variant, &defaults)?);
+    }
+    Ok(cases)
+}
+
+/// The items generated by `#[derive(Bind)]`: queries in an inherent impl, trait impls, and any supporting types
+pub fn expand(ast: &syn::DeriveInput) -> Result<TokenStream> {
+    let Data::Enum(data_enum) = &ast.data else { return Err(Error::new_spanned(ast, "#[derive(Bind)] only applicable to enums")); };
+    let cases = enum_cases(ast)?;
+
+    let name = &ast.ident;
+    let error_name = format_ident!("{name}QueryError");
+
+    let mut functions = Vec::new();
+    let mut items = Vec::new();
+
+    let mut schema = SchemaSpec { types: Vec::new() };
+    for attr in &ast.attrs {
+        if !attr.path().is_ident("bindings") { continue; }
+        schema.types.extend(attr.parse_args::<SchemaSpec>()?.types);
+    }
+    if !schema.types.is_empty() {
+        schema.validate(&cases)?;
+        functions.push(schema.gen_functions(&ast.vis, &cases, &error_name)?);
+    }
+
+    for attr in &ast.attrs {
+        if !attr.path().is_ident("query") { continue; }
+        if attr.parse_args_with(TraitSpec::peek)? {
+            let mut spec = attr.parse_args::<TraitSpec>()?;
+            for function in &mut spec.functions {
+                function.infer_types(&cases, &schema, &error_name)?;
+                function.validate(&cases)?;
+            }
+            items.push(spec.gen_impl(name, &ast.generics, &cases, &error_name)?);
+            continue;
+        }
+        let mut spec = parse2::<FunctionSpec>(attr.meta.to_token_stream())?;
+        spec.infer_types(&cases, &schema, &error_name)?;
+        spec.validate(&cases)?;
+        functions.push(spec.gen_function(&cases, &error_name)?);
+    }
+
+    for attr in &ast.attrs {
+        if !attr.path().is_ident("bind_row") { continue; }
+        let spec = attr.parse_args::<ItemSpec>()?;
+        let (item, row_functions) = spec.gen_row(&ast.vis, &cases)?;
+        items.push(item);
+        functions.push(row_functions);
+    }
+
+    for attr in &ast.attrs {
+        if !attr.path().is_ident("bind_query") { continue; }
+        let spec = attr.parse_args::<ItemSpec>()?;
+        let (item, query_functions) = spec.gen_query(&ast.vis, name, &cases, &error_name)?;
+        items.push(item);
+        functions.push(query_functions);
+    }
+
+    // Conversions from `Self` share one accessor per binding
+    let mut accessors = BTreeMap::new();
+    for attr in &ast.attrs {
+        if !attr.path().is_ident("bind_impl") { continue; }
+        for spec in attr.parse_args_with(Punctuated::<ImplSpec, Token![,]>::parse_terminated)? {
+            if let Some(mut accessor) = spec.accessor() {
+                accessor.infer_types(&cases, &schema, &error_name)?;
+                accessor.validate(&cases)?;
+                accessors.insert(spec.binding().clone(), accessor.gen_function(&cases, &error_name)?);
+            }
+            items.push(spec.gen_impl(name, &ast.generics, &cases, &error_name)?);
+        }
+    }
+    functions.extend(accessors.into_values());
+
+    for attr in &ast.attrs {
+        if !attr.path().is_ident("bind_serde") { continue; }
+        if !cfg!(feature = "serde") {
+            return Err(Error::new_spanned(attr, r#"#[bind_serde(...)] requires the "serde" feature of enum_bind"#));
+        }
+        // Fields of a generic type would need serde bounds the derive cannot guess
+        if !ast.generics.params.is_empty() {
+            return Err(Error::new_spanned(&ast.generics, "#[bind_serde(...)] does not support generic enums"));
+        }
+        let spec = SerdeSpec { key: attr.parse_args::<Ident>()? };
+        let (item, lookup) = spec.gen_impls(name, &cases, &schema, &error_name)?;
+        items.push(item);
+        functions.push(lookup);
+    }
+
+    let generics = &ast.generics;
+    let generic_params = &generics.params;
+
+    items.push(gen_bindable(name, &ast.generics, data_enum, &cases));
+
+    let default_error = gen_default_error(&ast.vis, &error_name);
+
+    let result = quote! {
+        impl <#generic_params> #name #generics {
+            #(#functions)*
+        }
+
+        #(#items)*
+
+        #default_error
+    };
+
+    Ok(result)
+}
+
+pub(crate) fn gen_bindable(name: &Ident, generics: &Generics, data_enum: &syn::DataEnum, cases: &Vec<Case>) -> TokenStream {
+    let variants = data_enum.variants.iter().map(|variant| variant.ident.to_string());
+    let bindings: BTreeSet<String> = cases.iter()
+        .flat_map(|case| case.bindings.values())
+        .filter(|binding| !matches!(binding, Binding::Flatten { .. }))
+        .map(|binding| binding.name().to_string())
+        .collect();
+    let rows = cases.iter().map(|case| {
+        let variant = case.variant.ident.to_string();
+        let values = case.bindings.keys()
+            .filter_map(|name| Some((name.to_string(), case.binding_value_string(name)?)))
+            .map(|(name, value)| quote! { (#name, #value) });
+        quote! { (#variant, &[#(#values),*]) }
+    });
+
+    let generic_params = &generics.params;
+    quote! {
+        impl <#generic_params> ::enum_bind::Bindable for #name #generics {
+            const VARIANTS: &'static [&'static str] = &[#(#variants),*];
+            const BINDINGS: &'static [&'static str] = &[#(#bindings),*];
+            const TABLE: &'static [(&'static str, &'static [(&'static str, &'static str)])] = &[#(#rows),*];
+        }
+    }
+}
+
+// Every enum names the shared error, whether or not any of its queries return it
+pub(crate) fn gen_default_error(visibility: &Visibility, error_name: &Ident) -> TokenStream {
+    quote! {
+        #[allow(dead_code)]
+        #visibility type #error_name = ::enum_bind::QueryError;
+    }
+}
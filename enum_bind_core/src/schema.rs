@@ -0,0 +1,81 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    Error, Result, Token, Type, Visibility, parse::{Parse, ParseStream}, parse_quote
+};
+
+use crate::{binding::Binding, case::{Case, get_field_name}, function_spec::FunctionSpec, types::{argument_type, option_type}};
+
+/// Parsed from `#[bindings(name: Type, ...)]`: every binding the enum may use, with `Option` for those some variants lack
+#[derive(Default)]
+pub struct SchemaSpec {
+    /// The declared bindings in order, with the type every variant's value must have
+    pub types: Vec<(Ident, Type)>,
+}
+
+impl SchemaSpec {
+    pub(crate) fn validate(&self, cases: &Vec<Case>) -> Result<()> {
+        for case in cases {
+            for (name, binding) in &case.bindings {
+                if matches!(binding, Binding::Field { .. } | Binding::Flatten { .. }) { continue; }
+                if !self.types.iter().any(|(declared, _)| declared == name) {
+                    return Err(Error::new_spanned(name,
+                        format!(r#"Binding "{name}" is not declared in #[bindings(...)]"#)));
+                }
+            }
+        }
+
+        for (name, ty) in &self.types {
+            if option_type(ty).is_some() { continue; }
+            if let Some(case) = cases.iter().find(|case| case.gen_binding_value(name).is_none() && case.flatten().is_none()) {
+                let variant = &case.variant.ident;
+                return Err(Error::new_spanned(variant,
+                    format!(r#"Variant "{variant}" does not have binding "{name}", which #[bindings(...)] declares as required"#)));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn gen_functions(&self, visibility: &Visibility, cases: &Vec<Case>, default_error: &Ident) -> Result<TokenStream> {
+        let mut functions = Vec::new();
+        for (name, ty) in &self.types {
+            let accessor: FunctionSpec = match option_type(ty) {
+                Some(_) => parse_quote! { query(#visibility fn #name(&self) -> #ty) },
+                None => parse_quote! { query(#visibility fn #name(&self) -> #ty, return = Strict) },
+            };
+            accessor.validate(cases)?;
+            functions.push(accessor.gen_function(cases, default_error)?);
+
+            // Only variants whose fields are all filled in by this binding can be built from it
+            let lookup_cases: Vec<_> = cases.iter()
+                .filter(|case| case.gen_binding_value(name).is_some())
+                .filter(|case| case.variant.fields.iter().enumerate()
+                    .all(|field| case.binding_field(name) == Some(&get_field_name(&field))))
+                .cloned()
+                .collect();
+            let mut input_type = option_type(ty).unwrap_or(ty).clone();
+            if lookup_cases.iter().all(|case| case.binding_field(name).is_none()) {
+                input_type = argument_type(&input_type);
+            }
+            let lookup_name = format_ident!("by_{name}");
+            let lookup: FunctionSpec = parse_quote! { query(#visibility fn #lookup_name(#name: #input_type) -> Vec<Self>, return = Vec) };
+            lookup.validate(&lookup_cases)?;
+            functions.push(lookup.gen_function(&lookup_cases, default_error)?);
+        }
+        Ok(quote! { #(#functions)* })
+    }
+}
+
+impl Parse for SchemaSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut types = Vec::new();
+        while !input.is_empty() {
+            let binding = input.parse::<Ident>()?;
+            input.parse::<Token![:]>()?;
+            types.push((binding, input.parse::<Type>()?));
+            if input.is_empty() { break; }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(SchemaSpec { types })
+    }
+}
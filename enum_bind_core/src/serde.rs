@@ -0,0 +1,161 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{
+    Error, LitStr, Result, Type, parse_quote
+};
+
+use crate::{binding::Binding, case::{Case, get_field_name}, function_spec::FunctionSpec, schema::SchemaSpec, types::{argument_type, binding_type}};
+
+// Parsed from `#[bind_serde(key)]`: variants are written as the value of `key`, or as a map of their bindings if they have fields.
+// Reading relies on `deserialize_any`, so only self-describing formats such as JSON are supported.
+pub(crate) struct SerdeSpec {
+    pub(crate) key: Ident,
+}
+
+impl SerdeSpec {
+    pub(crate) fn gen_impls(&self, enum_name: &Ident, cases: &Vec<Case>, schema: &SchemaSpec, default_error: &Ident) -> Result<(TokenStream, TokenStream)> {
+        let key = &self.key;
+        let key_name = LitStr::new(&key.to_string(), key.span());
+        let key_type = binding_type(cases, schema, key)?;
+        // Borrowed strings cannot outlive the input, so they are read as `String` and matched as `&str`
+        let (key_read, key_access) = match &key_type {
+            Type::Reference(reference) if matches!(reference.elem.as_ref(), Type::Path(path) if path.path.is_ident("str")) =>
+                (quote! { String }, quote! { value.as_str() }),
+            _ => (quote! { #key_type }, quote! { value }),
+        };
+        let key_arg = argument_type(&key_type);
+
+        let mut serialize_arms = Vec::new();
+        let mut seen_variants = Vec::new();
+        for case in cases {
+            if seen_variants.contains(&&case.variant.ident) { continue; }
+            seen_variants.push(&case.variant.ident);
+
+            let variant = &case.variant.ident;
+            let Some(key_value) = case.gen_binding_value(key) else {
+                return Err(Error::new_spanned(variant,
+                    format!(r#"Variant "{variant}" has no value for binding "{key}", so it cannot be serialized"#)));
+            };
+            let pattern = case.gen_self_expr();
+            if case.variant.fields.is_empty() {
+                serialize_arms.push(quote! { #pattern => ::enum_bind::__serde::Serialize::serialize(&#key_value, serializer) });
+                continue;
+            }
+
+            let mut names = vec![key_name.clone()];
+            let mut values = vec![key_value];
+            for name in case.bindings.keys().filter(|name| *name != key) {
+                let Some(value) = case.gen_binding_value(name) else { continue; };
+                names.push(LitStr::new(&name.to_string(), name.span()));
+                values.push(value);
+            }
+            let len = names.len();
+            serialize_arms.push(quote! {
+                #pattern => {
+                    let mut map = ::enum_bind::__serde::Serializer::serialize_map(serializer, Some(#len))?;
+                    #(::enum_bind::__serde::ser::SerializeMap::serialize_entry(&mut map, #names, &#values)?;)*
+                    ::enum_bind::__serde::ser::SerializeMap::end(map)
+                }
+            });
+        }
+
+        // Deserializing reads the key and every field a variant may have, then finds the variant through a lookup
+        // taking them all as optional arguments, so fields only match the variants that store them
+        let mut fields: Vec<(Ident, Type)> = Vec::new();
+        for case in cases {
+            if let Some(Binding::Field { .. }) = case.bindings.get(key) {
+                let variant = &case.variant.ident;
+                return Err(Error::new_spanned(key,
+                    format!(r#"Binding "{key}" of variant "{variant}" comes from a field, so it cannot identify the variant"#)));
+            }
+            for field in case.variant.fields.iter().enumerate() {
+                let name = get_field_name(&field);
+                match fields.iter().find(|(seen, _)| *seen == name) {
+                    Some((_, ty)) if *ty != field.1.ty => return Err(Error::new_spanned(&field.1.ty,
+                        format!(r#"Field "{name}" has another type in a different variant, so #[bind_serde(...)] cannot read it"#))),
+                    Some(_) => {},
+                    None => fields.push((name, field.1.ty.clone())),
+                }
+            }
+        }
+        let (field_names, field_types): (Vec<_>, Vec<_>) = fields.into_iter().unzip();
+        let field_keys: Vec<_> = field_names.iter().map(|field| LitStr::new(&field.to_string(), field.span())).collect();
+
+        let lookup: FunctionSpec = parse_quote! {
+            query(fn __bind_serde_lookup(#key: Option<#key_arg>, #(#field_names: Option<#field_types>),*) -> Option<Self>, optional_args)
+        };
+        lookup.validate(cases)?;
+        let lookup = lookup.gen_function(cases, default_error)?;
+        let no_fields = field_names.iter().map(|_| quote! { None });
+
+        let expecting = LitStr::new(&format!(r#"a "{key}" binding of {enum_name}, or a map of its bindings"#), key.span());
+        let scalars = [
+            (quote! { visit_bool }, quote! { bool }),
+            (quote! { visit_i64 }, quote! { i64 }),
+            (quote! { visit_u64 }, quote! { u64 }),
+            (quote! { visit_f64 }, quote! { f64 }),
+            (quote! { visit_char }, quote! { char }),
+            (quote! { visit_str }, quote! { &str }),
+        ];
+        let visit_scalars = scalars.iter().map(|(method, ty)| quote! {
+            fn #method<__E: ::enum_bind::__serde::de::Error>(self, v: #ty) -> Result<#enum_name, __E> {
+                let value = <#key_read as ::enum_bind::__serde::Deserialize>::deserialize(
+                    ::enum_bind::__serde::de::IntoDeserializer::<'de, __E>::into_deserializer(v))?;
+                Self::unit(value)
+            }
+        });
+
+        let item = quote! {
+            impl ::enum_bind::__serde::Serialize for #enum_name {
+                fn serialize<__S: ::enum_bind::__serde::Serializer>(&self, serializer: __S) -> Result<__S::Ok, __S::Error> {
+                    match self {
+                        #(#serialize_arms,)*
+                    }
+                }
+            }
+
+            impl<'de> ::enum_bind::__serde::Deserialize<'de> for #enum_name {
+                #[allow(unused_variables, unreachable_patterns)]
+                fn deserialize<__D: ::enum_bind::__serde::Deserializer<'de>>(deserializer: __D) -> Result<Self, __D::Error> {
+                    struct __Visitor;
+
+                    impl __Visitor {
+                        fn unit<__E: ::enum_bind::__serde::de::Error>(value: #key_read) -> Result<#enum_name, __E> {
+                            #enum_name::__bind_serde_lookup(Some(#key_access), #(#no_fields),*)
+                                .ok_or_else(|| __E::custom(format_args!("unknown {} {:?}", #key_name, value)))
+                        }
+                    }
+
+                    impl<'de> ::enum_bind::__serde::de::Visitor<'de> for __Visitor {
+                        type Value = #enum_name;
+
+                        fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                            formatter.write_str(#expecting)
+                        }
+
+                        #(#visit_scalars)*
+
+                        fn visit_map<__A: ::enum_bind::__serde::de::MapAccess<'de>>(self, mut map: __A) -> Result<#enum_name, __A::Error> {
+                            let mut value: Option<#key_read> = None;
+                            #(let mut #field_names: Option<#field_types> = None;)*
+                            while let Some(entry) = ::enum_bind::__serde::de::MapAccess::next_key::<String>(&mut map)? {
+                                match entry.as_str() {
+                                    #key_name => value = Some(::enum_bind::__serde::de::MapAccess::next_value(&mut map)?),
+                                    #(#field_keys => #field_names = Some(::enum_bind::__serde::de::MapAccess::next_value(&mut map)?),)*
+                                    _ => { ::enum_bind::__serde::de::MapAccess::next_value::<::enum_bind::__serde::de::IgnoredAny>(&mut map)?; },
+                                }
+                            }
+                            let value = value.ok_or_else(|| <__A::Error as ::enum_bind::__serde::de::Error>::missing_field(#key_name))?;
+                            #enum_name::__bind_serde_lookup(Some(#key_access), #(#field_names),*)
+                                .ok_or_else(|| <__A::Error as ::enum_bind::__serde::de::Error>::custom(
+                                    format_args!("unknown {} {:?}, or missing fields for it", #key_name, value)))
+                        }
+                    }
+
+                    ::enum_bind::__serde::Deserializer::deserialize_any(deserializer, __Visitor)
+                }
+            }
+        };
+        Ok((item, lookup))
+    }
+}
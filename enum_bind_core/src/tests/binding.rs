@@ -0,0 +1,83 @@
+use syn::{parse_quote, parse2};
+use quote::{format_ident, quote};
+
+use crate::Binding;
+
+#[test]
+fn test_parse_binding_expr_pat() {
+    assert_eq!(
+        parse2::<Binding>(quote! { role = "engineer" }).unwrap(),
+        Binding::Expr {
+            name: format_ident!("role"),
+            expr: parse_quote! { "engineer" },
+            guard: None,
+        }
+    );
+}
+
+#[test]
+fn test_parse_binding_only_pat() {
+    assert_eq!(
+        parse2::<Binding>(quote! { role = Some(_) }).unwrap(),
+        Binding::Expr {
+            name: format_ident!("role"),
+            expr: parse_quote! { Some(_) },
+            guard: None,
+        }
+    );
+}
+
+#[test]
+fn test_parse_binding_only_expr() {
+    assert_eq!(
+        parse2::<Binding>(quote! { a = 2 + 2 }).unwrap(),
+        Binding::Expr {
+            name: format_ident!("a"),
+            expr: parse_quote! { 2 + 2 },
+            guard: None,
+        }
+    );
+}
+
+#[test]
+fn test_parse_binding_pattern() {
+    assert_eq!(
+        parse2::<Binding>(quote! { status = 200..=299 }).unwrap(),
+        Binding::Pattern {
+            name: format_ident!("status"),
+            pat: parse_quote! { 200..=299 },
+            guard: None,
+        }
+    );
+    assert_eq!(
+        parse2::<Binding>(quote! { kind = "fn" | "function" }).unwrap(),
+        Binding::Pattern {
+            name: format_ident!("kind"),
+            pat: parse_quote! { "fn" | "function" },
+            guard: None,
+        }
+    );
+}
+
+#[test]
+fn test_parse_binding_guard() {
+    assert_eq!(
+        parse2::<Binding>(quote! { n = x if x > 0 }).unwrap(),
+        Binding::Expr {
+            name: format_ident!("n"),
+            expr: parse_quote! { x },
+            guard: Some(parse_quote! { x > 0 }),
+        }
+    );
+}
+
+#[test]
+fn test_parse_binding_flatten() {
+    assert_eq!(
+        parse2::<Binding>(quote! { flatten = _0 }).unwrap(),
+        Binding::Flatten {
+            name: format_ident!("flatten"),
+            field: format_ident!("_0"),
+        }
+    );
+}
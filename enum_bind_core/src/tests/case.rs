@@ -10,7 +10,7 @@ fn parse_empty() {
     let variant: &Variant = &parse_quote! {
         #[bind()] FooBar
     };
-    let case = get_cases(&variant).unwrap().pop().unwrap();
+    let case = get_cases(variant, &[]).unwrap().pop().unwrap();
     assert_eq!(case, Case {
         variant,
         bindings: BTreeMap::new(),
@@ -22,7 +22,7 @@ fn parse_column_value() {
     let variant: &Variant = &parse_quote! {
         #[bind(foo = "value")] FooBar
     };
-    let case = get_cases(&variant).unwrap().pop().unwrap();
+    let case = get_cases(variant, &[]).unwrap().pop().unwrap();
     assert_eq!(case, Case {
         variant,
         bindings: {
@@ -30,6 +30,7 @@ fn parse_column_value() {
             map.insert(format_ident!("foo"), Binding::Expr {
                 name: format_ident!("foo"),
                 expr: parse_quote! { "value" },
+                guard: None,
             });
             map
         }
@@ -41,7 +42,7 @@ fn parse_column_expr() {
     let variant: &Variant = &parse_quote! {
         #[bind(foo = 2 + 2)] FooBar
     };
-    let case = get_cases(&variant).unwrap().pop().unwrap();
+    let case = get_cases(variant, &[]).unwrap().pop().unwrap();
     assert_eq!(case, Case {
         variant,
         bindings: {
@@ -49,6 +50,7 @@ fn parse_column_expr() {
             map.insert(format_ident!("foo"), Binding::Expr {
                 name: format_ident!("foo"),
                 expr: parse_quote! { 2 + 2 },
+                guard: None,
             });
             map
         }
@@ -60,7 +62,7 @@ fn parse_column_pattern() {
     let variant: &Variant = &parse_quote! {
         #[bind(foo = Some(_))] FooBar
     };
-    let case = get_cases(&variant).unwrap().pop().unwrap();
+    let case = get_cases(variant, &[]).unwrap().pop().unwrap();
     assert_eq!(case, Case {
         variant,
         bindings: {
@@ -68,6 +70,7 @@ fn parse_column_pattern() {
             map.insert(format_ident!("foo"), Binding::Expr {
                 name: format_ident!("foo"),
                 expr: parse_quote! { Some(_) },
+                guard: None,
             });
             map
         }
@@ -79,7 +82,7 @@ fn parse_variant_field() {
     let variant: &Variant = &parse_quote! {
         #[bind()] FooBar { foo: usize }
     };
-    let case = get_cases(&variant).unwrap().pop().unwrap();
+    let case = get_cases(variant, &[]).unwrap().pop().unwrap();
     assert_eq!(case, Case {
         variant,
         bindings: {
@@ -95,8 +98,24 @@ fn parse_variant_field_override() {
     let variant: &Variant = &parse_quote! {
         #[bind(foo = "bar")] FooBar { foo: usize }
     };
-    let cases = get_cases(&variant);
-    assert_eq!(cases.is_err(), true);
+    let cases = get_cases(variant, &[]);
+    assert!(cases.is_err());
+}
+
+#[test]
+fn expand_needs_enum() {
+    let ast: syn::DeriveInput = parse_quote! { struct Environment { name: &'static str } };
+    assert!(crate::enum_cases(&ast).is_err());
+    assert!(crate::expand(&ast).is_err());
+}
+
+#[test]
+fn bind_serde_rejects_generics() {
+    let ast: syn::DeriveInput = parse_quote! {
+        #[bind_serde(name)]
+        enum Slot<T> { #[bind(name = "empty")] Empty, #[bind(name = "full")] Full(T) }
+    };
+    assert!(crate::expand(&ast).is_err());
 }
 
 // #[test]
@@ -106,7 +125,7 @@ fn parse_variant_field_override() {
 //         #[bind(bar = Some(19), qux = _, pff = 42)]
 //         FooBar { qux: &'static str, bar: Option<usize> }
 //     };
-//     let cases = get_cases(&variant).unwrap();
+//     let cases = get_cases(variant, &[]).unwrap();
 //     assert_eq!(cases[0], Relation {
 //         variant,
 //         columns: {
@@ -149,4 +168,31 @@ fn parse_variant_field_override() {
 //             map
 //         }
 //     });
-// }
\ No newline at end of file
+// }
+#[test]
+fn parse_defaults() {
+    let variant: &Variant = &parse_quote! {
+        #[bind(foo = "override")]
+        Variant { bar: i32 }
+    };
+    let defaults = [parse_quote! { foo = "default" }, parse_quote! { bar = 0 }, parse_quote! { baz = ! }];
+    let case = get_cases(variant, &defaults).unwrap().pop().unwrap();
+    assert_eq!(case.bindings[&format_ident!("foo")], parse_quote! { foo = "override" });
+    assert_eq!(case.bindings[&format_ident!("bar")], Binding::Field { name: format_ident!("bar") });
+    assert_eq!(case.bindings[&format_ident!("baz")], Binding::Never { name: format_ident!("baz") });
+}
+
+#[test]
+fn binding_value_strings() {
+    let variant: &Variant = &parse_quote! {
+        #[bind(name = "ok", code = 200u16, range = 1..=9, scaled = bar * 2, hidden = !)]
+        Variant { bar: i32 }
+    };
+    let case = get_cases(variant, &[]).unwrap().pop().unwrap();
+    assert_eq!(case.binding_value_string(&format_ident!("name")), Some("ok".to_string()));
+    assert_eq!(case.binding_value_string(&format_ident!("code")), Some("200".to_string()));
+    assert_eq!(case.binding_value_string(&format_ident!("range")), Some("1..=9".to_string()));
+    assert_eq!(case.binding_value_string(&format_ident!("scaled")), None);
+    assert_eq!(case.binding_value_string(&format_ident!("bar")), None);
+    assert_eq!(case.binding_value_string(&format_ident!("hidden")), None);
+}
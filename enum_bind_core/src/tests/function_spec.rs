@@ -0,0 +1,343 @@
+use quote::format_ident;
+use syn::{Variant, parse_quote};
+
+use crate::{Case, FunctionSpec, ImplSpec, OutputMode, SchemaSpec, TraitSpec, get_cases};
+
+fn cases(variants: &[Variant]) -> Vec<Case<'_>> {
+    variants.iter().flat_map(|variant| get_cases(variant, &[]).unwrap()).collect()
+}
+
+#[test]
+fn self_to_arg() {
+    let spec: FunctionSpec = parse_quote! {
+        query(pub fn foo(self) -> i32, return = Strict)
+    };
+    assert_eq!(spec, FunctionSpec {
+        visibility: parse_quote! { pub },
+        signature: parse_quote! { fn foo(self) -> i32 },
+        output_mode: OutputMode::Strict,
+        output_names: vec![],
+        condition: None,
+        error_type: None,
+        optional_args: false,
+    });
+}
+
+#[test]
+fn arg_to_self() {
+    let spec: FunctionSpec = parse_quote! {
+        query(pub fn from_foo(foo: i32) -> Self, return = Strict)
+    };
+    assert_eq!(spec, FunctionSpec {
+        visibility: parse_quote! { pub },
+        signature: parse_quote! { fn from_foo(foo: i32) -> Self },
+        output_mode: OutputMode::Strict,
+        output_names: vec![],
+        condition: None,
+        error_type: None,
+        optional_args: false,
+    });
+}
+
+#[test]
+fn self_and_arg_to_arg() {
+    let spec: FunctionSpec = parse_quote! {
+        query(pub fn check_foo(&self, foo: i32) -> Option<i32>, return = Option(foo))
+    };
+    assert_eq!(spec, FunctionSpec {
+        visibility: parse_quote! { pub },
+        signature: parse_quote! { fn check_foo(&self, foo: i32) -> Option<i32> },
+        output_mode: OutputMode::Option,
+        output_names: vec![parse_quote! { foo }],
+        condition: None,
+        error_type: None,
+        optional_args: false,
+    });
+}
+
+#[test]
+fn result_with_error_type() {
+    let spec: FunctionSpec = parse_quote! {
+        query(pub fn by_foo(foo: i32) -> Result<Self, MyError>, return = Result(_, MyError))
+    };
+    assert_eq!(spec, FunctionSpec {
+        visibility: parse_quote! { pub },
+        signature: parse_quote! { fn by_foo(foo: i32) -> Result<Self, MyError> },
+        output_mode: OutputMode::Result,
+        output_names: vec![],
+        condition: None,
+        error_type: Some(parse_quote! { MyError }),
+        optional_args: false,
+    });
+}
+
+#[test]
+fn bool_with_condition() {
+    let spec: FunctionSpec = parse_quote! {
+        query(pub fn is_prod(&self) -> bool, return = Bool(data_realm = "prod"))
+    };
+    assert_eq!(spec, FunctionSpec {
+        visibility: parse_quote! { pub },
+        signature: parse_quote! { fn is_prod(&self) -> bool },
+        output_mode: OutputMode::Bool,
+        output_names: vec![parse_quote! { data_realm }],
+        condition: Some(parse_quote! { "prod" }),
+        error_type: None,
+        optional_args: false,
+    });
+}
+
+#[test]
+fn single_rejects_obvious_overlap() {
+    let variants: Vec<Variant> = vec![
+        parse_quote! { #[bind(kind = "fn")] Function },
+        parse_quote! { #[bind(kind = "struct")] Struct },
+        parse_quote! { #[bind(kind = "fn")] Method },
+    ];
+    let mut cases = cases(&variants);
+
+    let spec: FunctionSpec = parse_quote! {
+        query(fn by_kind(kind: &str) -> Result<Option<Self>, MyError>, return = Single)
+    };
+    assert!(spec.validate(&cases).is_err());
+    cases.pop();
+    assert!(spec.validate(&cases).is_ok());
+
+    let variants: Vec<Variant> = vec![
+        parse_quote! { #[bind(code = 200)] Ok },
+        parse_quote! { Custom { code: u16 } },
+    ];
+    let spec: FunctionSpec = parse_quote! {
+        query(fn by_code(code: u16) -> Result<Option<Self>, MyError>, return = Single)
+    };
+    let error = spec.validate(&self::cases(&variants)).unwrap_err().to_string();
+    assert_eq!(error, r#"Variants "Ok" and "Custom" both match code = 200, so function "by_code" would be ambiguous"#);
+}
+
+#[test]
+fn multiple_outputs() {
+    let spec: FunctionSpec = parse_quote! {
+        query(fn realm_and_stage(&self) -> (&'static str, &'static str), return = Strict(data_realm, push_stage))
+    };
+    assert_eq!(spec.output_names, vec![format_ident!("data_realm"), format_ident!("push_stage")]);
+
+    let spec: FunctionSpec = parse_quote! {
+        query(fn realm_and_stage(&self) -> Result<(i32, i32), E>, return = Result((a, b), E))
+    };
+    assert_eq!(spec.output_names, vec![format_ident!("a"), format_ident!("b")]);
+    assert_eq!(spec.error_type, Some(parse_quote! { E }));
+
+    let spec: FunctionSpec = parse_quote! {
+        query(fn realm_and_stage(&self) -> (&'static str, &'static str), return = Strict((data_realm, push_stage)))
+    };
+    assert_eq!(spec.output_names, vec![format_ident!("data_realm"), format_ident!("push_stage")]);
+
+    let spec = syn::parse2::<FunctionSpec>(quote::quote! {
+        query(fn realm_and_stage(&self) -> Result<(i32, i32), E>, return = Result(a, b))
+    });
+    assert!(spec.is_err());
+}
+
+#[test]
+fn mut_borrow_requires_storage() {
+    let variants: Vec<Variant> = vec![
+        parse_quote! { Function { name: String } },
+        parse_quote! { #[bind(name = struct_name)] Struct { struct_name: String } },
+        parse_quote! { #[bind(name = String::new())] Marker },
+    ];
+    let mut cases = cases(&variants);
+
+    let spec: FunctionSpec = parse_quote! {
+        query(fn name_mut(&mut self) -> Option<&mut String>, return = Option(name))
+    };
+    assert!(spec.validate(&cases).is_err());
+    cases.pop();
+    assert!(spec.validate(&cases).is_ok());
+}
+
+#[test]
+fn set_requires_mut_self_and_argument() {
+    let variant: Variant = parse_quote! { Function { name: String } };
+    let cases = get_cases(&variant, &[]).unwrap();
+
+    let spec: FunctionSpec = parse_quote! {
+        query(fn set_name(&mut self, name: String) -> bool, return = Set(name))
+    };
+    assert!(spec.validate(&cases).is_ok());
+
+    let spec: FunctionSpec = parse_quote! {
+        query(fn set_name(&self, name: String) -> bool, return = Set(name))
+    };
+    assert!(spec.validate(&cases).is_err());
+
+    let spec: FunctionSpec = parse_quote! {
+        query(fn set_name(&mut self, value: String) -> bool, return = Set(name))
+    };
+    assert!(spec.validate(&cases).is_err());
+}
+
+#[test]
+fn optional_args() {
+    let variants: Vec<Variant> = vec![
+        parse_quote! { #[bind(kind = "fn")] Function },
+        parse_quote! { Marker },
+    ];
+    let cases = cases(&variants);
+
+    let spec: FunctionSpec = parse_quote! {
+        query(fn find(kind: Option<&str>) -> Vec<Self>, return = Vec, optional_args)
+    };
+    assert!(spec.optional_args);
+    assert_eq!(spec.output_mode, OutputMode::Vec);
+    assert!(spec.validate(&cases).is_ok());
+
+    let spec: FunctionSpec = parse_quote! {
+        query(fn find(kind: Option<&str>) -> Vec<Self>, return = Vec)
+    };
+    assert!(spec.validate(&cases).is_err());
+}
+
+#[test]
+fn pattern_binding_is_not_an_output() {
+    let variant: Variant = parse_quote! { #[bind(status = 200..=299)] Success };
+    let cases = get_cases(&variant, &[]).unwrap();
+
+    let spec: FunctionSpec = parse_quote! { query(fn status(&self) -> Option<u16>) };
+    assert!(spec.validate(&cases).is_err());
+
+    let spec: FunctionSpec = parse_quote! { query(fn is_status(&self, code: u16) -> bool, return = Bool(status = code)) };
+    assert!(spec.validate(&cases).is_ok());
+}
+
+#[test]
+fn guarded_binding_is_not_an_output() {
+    let variants: Vec<Variant> = vec![
+        parse_quote! { #[bind(n = x if x > 0)] Positive },
+        parse_quote! { #[bind(n = s if s > 0)] Sized { s: i32 } },
+    ];
+    let cases = cases(&variants);
+
+    let spec: FunctionSpec = parse_quote! { query(fn n(&self) -> Option<i32>) };
+    assert!(spec.validate(&cases).is_err());
+    assert!(spec.validate(&cases[1..].to_vec()).is_ok());
+}
+
+#[test]
+fn schema_checks_bindings() {
+    let variants: Vec<Variant> = vec![
+        parse_quote! { #[bind(data_realm = "prod", push_stage = "prod")] Prod },
+        parse_quote! { #[bind(data_realm = "test")] IntegrationTests },
+    ];
+    let cases = cases(&variants);
+
+    let schema: SchemaSpec = parse_quote! { data_realm: &'static str, push_stage: Option<&'static str> };
+    assert!(schema.validate(&cases).is_ok());
+
+    let schema: SchemaSpec = parse_quote! { data_realm: &'static str, push_stage: &'static str };
+    assert!(schema.validate(&cases).is_err());
+
+    let schema: SchemaSpec = parse_quote! { data_realm: &'static str };
+    assert!(schema.validate(&cases).is_err());
+}
+
+#[test]
+fn shorthand_infers_types() {
+    let variants: Vec<Variant> = vec![
+        parse_quote! { #[bind(push_stage = "prod", port = 8080u16)] Prod },
+        parse_quote! { #[bind(push_stage = "canary")] Canary },
+    ];
+    let cases = cases(&variants);
+    let schema = SchemaSpec { types: vec![] };
+    let error = format_ident!("EnvironmentQueryError");
+
+    let mut spec: FunctionSpec = parse_quote! { query(pub push_stage) };
+    spec.infer_types(&cases, &schema, &error).unwrap();
+    assert_eq!(spec.signature, parse_quote! { fn push_stage(&self) -> Option<&'static str> });
+
+    let mut spec: FunctionSpec = parse_quote! { query(by port, return = Result) };
+    spec.infer_types(&cases, &schema, &error).unwrap();
+    assert_eq!(spec.signature, parse_quote! { fn by_port(port: u16) -> Result<Self, EnvironmentQueryError> });
+
+    let mut spec: FunctionSpec = parse_quote! { query(by push_stage) };
+    spec.infer_types(&cases, &schema, &error).unwrap();
+    assert_eq!(spec.signature, parse_quote! { fn by_push_stage(push_stage: &str) -> Option<Self> });
+
+    let mut spec: FunctionSpec = parse_quote! { query(region) };
+    assert!(spec.infer_types(&cases, &schema, &error).is_err());
+
+    let schema: SchemaSpec = parse_quote! { region: Option<String> };
+    spec.infer_types(&cases, &schema, &error).unwrap();
+    assert_eq!(spec.signature, parse_quote! { fn region(&self) -> Option<String> });
+
+    // Variants disagreeing on the type leave it to be written out
+    let variants: Vec<Variant> = vec![
+        parse_quote! { #[bind(port = 8080u16)] Prod },
+        parse_quote! { #[bind(port = "none")] Local },
+    ];
+    let mismatched = self::cases(&variants);
+    let mut spec: FunctionSpec = parse_quote! { query(port) };
+    assert!(spec.infer_types(&mismatched, &schema, &error).is_err());
+}
+
+#[test]
+fn impl_spec_requires_binding_on_every_variant() {
+    let variants: Vec<Variant> = vec![
+        parse_quote! { #[bind(name = "prod")] Prod },
+        parse_quote! { Local },
+    ];
+    let cases = cases(&variants);
+    let error = format_ident!("EnvironmentQueryError");
+
+    let spec: ImplSpec = parse_quote! { FromStr = name };
+    assert!(spec.gen_impl(&format_ident!("Environment"), &Default::default(), &cases, &error).is_err());
+
+    let spec: ImplSpec = parse_quote! { From<u8> for &'static str = name };
+    assert!(spec.gen_impl(&format_ident!("Environment"), &Default::default(), &cases, &error).is_err());
+    let spec: ImplSpec = parse_quote! { From<Environment> for &'static str = name };
+    assert!(spec.gen_impl(&format_ident!("Environment"), &Default::default(), &cases, &error).is_ok());
+
+    let spec: ImplSpec = parse_quote! { From<Self> for &'static str = name };
+    let mut accessor = spec.accessor().unwrap();
+    accessor.infer_types(&cases, &SchemaSpec { types: vec![] }, &error).unwrap();
+    assert!(accessor.validate(&cases).is_err());
+
+    assert!(syn::parse2::<ImplSpec>(quote::quote! { Debug = name }).is_err());
+}
+
+#[test]
+fn trait_spec_parses_functions() {
+    let spec: TraitSpec = parse_quote! {
+        impl HasRealm {
+            fn data_realm(&self) -> &'static str, return = Strict(realm);
+            by realm, return = Vec;
+        }
+    };
+    assert_eq!(spec.path, parse_quote! { HasRealm });
+    assert_eq!(spec.functions.len(), 2);
+    assert_eq!(spec.functions[0].output_names, vec![format_ident!("realm")]);
+    assert_eq!(spec.functions[1].signature, parse_quote! { fn by_realm(realm: _) -> _ });
+    assert_eq!(spec.functions[1].output_mode, OutputMode::Vec);
+    assert_eq!(spec.functions[1].output_mode(), &OutputMode::Vec);
+    assert_eq!(spec.functions[0].output_names(), &[format_ident!("realm")]);
+    assert_eq!(spec.functions[0].signature().ident, "data_realm");
+
+    assert!(syn::parse2::<TraitSpec>(quote::quote! { impl HasRealm { pub fn data_realm(&self) -> &'static str } }).is_err());
+}
+
+#[test]
+fn flatten_constructor_needs_single_field() {
+    let variants: Vec<Variant> = vec![
+        parse_quote! { #[bind(flatten = inner)] Item { inner: ItemDecl, line: u32 } },
+    ];
+    let cases = cases(&variants);
+    let error = format_ident!("DeclQueryError");
+
+    let spec: FunctionSpec = parse_quote! { query(fn by_kind(kind: &str) -> Option<Self>) };
+    assert!(spec.gen_function(&cases, &error).is_err());
+
+    let spec: FunctionSpec = parse_quote! { query(fn has_kind(kind: &str) -> bool, return = Any) };
+    assert!(spec.gen_function(&cases, &error).is_ok());
+
+    let spec: FunctionSpec = parse_quote! { query(fn kind(&self) -> Option<&'static str>) };
+    assert!(spec.gen_function(&cases, &error).is_ok());
+}
@@ -0,0 +1,55 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::{
+    Error, Generics, Path, Result, Token, Visibility, braced, parse::{Parse, ParseStream}
+};
+
+use crate::{case::Case, function_spec::FunctionSpec};
+
+// Parsed from `#[query(impl Trait { ...; ... })]`: queries implementing a trait rather than going into the inherent impl
+pub(crate) struct TraitSpec {
+    pub(crate) path: Path,
+    pub(crate) functions: Vec<FunctionSpec>,
+}
+
+impl TraitSpec {
+    pub(crate) fn peek(input: ParseStream) -> Result<bool> {
+        let is_trait = input.peek(Token![impl]);
+        input.parse::<TokenStream>()?;
+        Ok(is_trait)
+    }
+
+    pub(crate) fn gen_impl(&self, name: &Ident, generics: &Generics, cases: &Vec<Case>, default_error: &Ident) -> Result<TokenStream> {
+        let mut functions = Vec::new();
+        for function in &self.functions {
+            functions.push(function.gen_function(cases, default_error)?);
+        }
+
+        let path = &self.path;
+        let generic_params = &generics.params;
+        Ok(quote! {
+            impl <#generic_params> #path for #name #generics {
+                #(#functions)*
+            }
+        })
+    }
+}
+
+impl Parse for TraitSpec {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<Token![impl]>()?;
+        let path = input.parse::<Path>()?;
+        let input = { let content; braced!(content in input); content };
+        let mut functions = Vec::new();
+        while !input.is_empty() {
+            let function = FunctionSpec::parse_body(&input)?;
+            if !matches!(function.visibility, Visibility::Inherited) {
+                return Err(Error::new_spanned(&function.visibility, "Trait impl functions cannot have a visibility"));
+            }
+            functions.push(function);
+            if !input.is_empty() { input.parse::<Token![;]>()?; }
+        }
+
+        Ok(TraitSpec { path, functions })
+    }
+}
@@ -0,0 +1,137 @@
+use proc_macro2::Ident;
+use syn::{
+    Error, Expr, GenericArgument, Lit, PathArguments, Result, Type, TypeParamBound, parse_quote
+};
+
+use crate::{binding::Binding, case::Case, schema::SchemaSpec};
+
+pub(crate) fn type_argument(ty: &Type, index: usize) -> Option<&Type> {
+    let Type::Path(type_path) = ty else { return None; };
+    let PathArguments::AngleBracketed(arguments) = &type_path.path.segments.last()?.arguments else { return None; };
+    arguments.args.iter()
+        .filter_map(|argument| match argument { GenericArgument::Type(ty) => Some(ty), _ => None })
+        .nth(index)
+}
+
+pub(crate) fn option_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else { return None; };
+    if type_path.path.segments.last()?.ident != "Option" { return None; }
+    type_argument(ty, 0)
+}
+
+pub(crate) fn iterator_item_type(ty: &Type) -> Option<&Type> {
+    let Type::ImplTrait(impl_trait) = ty else { return None; };
+    impl_trait.bounds.iter().find_map(|bound| {
+        let TypeParamBound::Trait(bound) = bound else { return None; };
+        let PathArguments::AngleBracketed(arguments) = &bound.path.segments.last()?.arguments else { return None; };
+        arguments.args.iter().find_map(|argument| match argument {
+            GenericArgument::AssocType(assoc) if assoc.ident == "Item" => Some(&assoc.ty),
+            _ => None,
+        })
+    })
+}
+
+// Whether the type holds a reference that is not 'static, and so may point into the enum
+pub(crate) fn is_borrowed_type(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => reference.lifetime.as_ref().is_none_or(|lifetime| lifetime.ident != "static"),
+        Type::Tuple(tuple) => tuple.elems.iter().any(is_borrowed_type),
+        Type::Paren(paren) => is_borrowed_type(&paren.elem),
+        Type::Group(group) => is_borrowed_type(&group.elem),
+        _ => false,
+    }
+}
+
+// Arguments are only matched against, so they need not be 'static unless they are stored in a field
+pub(crate) fn argument_type(ty: &Type) -> Type {
+    match ty {
+        Type::Reference(reference) if reference.lifetime.as_ref().is_some_and(|lifetime| lifetime.ident == "static") => {
+            let mut reference = reference.clone();
+            reference.lifetime = None;
+            Type::Reference(reference)
+        },
+        _ => ty.clone(),
+    }
+}
+
+// Whether the type is known to be `Copy`, so accessors can return it out of `&self`
+pub(crate) fn is_copy_type(ty: &Type) -> bool {
+    const PRIMITIVES: &[&str] = &["bool", "char", "u8", "u16", "u32", "u64", "u128", "usize",
+                                  "i8", "i16", "i32", "i64", "i128", "isize", "f32", "f64"];
+    match ty {
+        Type::Reference(reference) => reference.mutability.is_none(),
+        Type::Path(path) => path.path.get_ident().is_some_and(|ident| PRIMITIVES.iter().any(|primitive| ident == primitive)),
+        Type::Tuple(tuple) => tuple.elems.iter().all(is_copy_type),
+        Type::Paren(paren) => is_copy_type(&paren.elem),
+        _ => false,
+    }
+}
+
+// Guesses the type of a binding from the fields it is bound to, or from literals it is bound to.
+// Unsuffixed number literals give way to any other type; otherwise every variant has to agree.
+pub(crate) fn infer_binding_type(cases: &Vec<Case>, name: &Ident) -> Option<Type> {
+    let mut inferred: Option<(Type, bool)> = None;
+    for case in cases {
+        let is_unsuffixed = |lit: &Lit| match lit {
+            Lit::Int(int) => int.suffix().is_empty(),
+            Lit::Float(float) => float.suffix().is_empty(),
+            _ => false,
+        };
+        let (ty, is_exact): (Type, bool) = match case.bindings.get(name) {
+            Some(Binding::Field { name }) => (case.field(name)?.ty.clone(), true),
+            Some(Binding::Expr { expr: Expr::Path(path), .. }) => match path.path.get_ident().and_then(|ident| case.field(ident)) {
+                Some(field) => (field.ty.clone(), true),
+                None => continue,
+            },
+            Some(Binding::Expr { expr: Expr::Lit(lit), .. }) => match literal_type(&lit.lit) {
+                Some(ty) => (ty, !is_unsuffixed(&lit.lit)),
+                None => continue,
+            },
+            Some(Binding::Pattern { pat, .. }) => match pattern_literal(pat).and_then(|lit| Some((literal_type(lit)?, !is_unsuffixed(lit)))) {
+                Some(inferred) => inferred,
+                None => continue,
+            },
+            _ => continue,
+        };
+        match &inferred {
+            None | Some((_, false)) if is_exact => inferred = Some((ty, true)),
+            None => inferred = Some((ty, false)),
+            Some((inferred, true)) if is_exact && *inferred != ty => return None,
+            Some(_) => {},
+        }
+    }
+    inferred.map(|(ty, _)| ty)
+}
+
+pub(crate) fn binding_type(cases: &Vec<Case>, schema: &SchemaSpec, name: &Ident) -> Result<Type> {
+    if let Some((_, ty)) = schema.types.iter().find(|(declared, _)| declared == name) {
+        return Ok(option_type(ty).unwrap_or(ty).clone());
+    }
+    infer_binding_type(cases, name).ok_or_else(|| Error::new_spanned(name,
+        format!(r#"Cannot infer the type of binding "{name}", write out the full signature of the query"#)))
+}
+
+pub(crate) fn literal_type(lit: &Lit) -> Option<Type> {
+    Some(match lit {
+        Lit::Str(_) => parse_quote! { &'static str },
+        Lit::ByteStr(_) => parse_quote! { &'static [u8] },
+        Lit::Byte(_) => parse_quote! { u8 },
+        Lit::Char(_) => parse_quote! { char },
+        Lit::Bool(_) => parse_quote! { bool },
+        Lit::Int(int) if int.suffix().is_empty() => parse_quote! { i32 },
+        Lit::Int(int) => syn::parse_str(int.suffix()).ok()?,
+        Lit::Float(float) if float.suffix().is_empty() => parse_quote! { f64 },
+        Lit::Float(float) => syn::parse_str(float.suffix()).ok()?,
+        _ => return None,
+    })
+}
+
+// The literal at either end of a range, or in the first alternative of an or-pattern
+pub(crate) fn pattern_literal(pat: &Expr) -> Option<&Lit> {
+    match pat {
+        Expr::Lit(lit) => Some(&lit.lit),
+        Expr::Range(range) => pattern_literal(range.start.as_deref().or(range.end.as_deref())?),
+        Expr::Binary(binary) => pattern_literal(&binary.left),
+        _ => None,
+    }
+}
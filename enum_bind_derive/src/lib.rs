@@ -0,0 +1,8 @@
+use syn::Error;
+
+#[proc_macro_derive(Bind, attributes(query, bind, bind_default, bind_impl, bind_row, bind_query, bind_serde, bindings))]
+pub fn derive_query(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    enum_bind_core::expand(&syn::parse(input).expect("Failed to parse macro input"))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
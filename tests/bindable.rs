@@ -0,0 +1,64 @@
+use enum_bind::{Bind, Bindable};
+
+#[derive(Bind, Debug, PartialEq)]
+#[allow(dead_code)]
+enum Status {
+    #[bind(name = "ok", code = 200)] Ok,
+    #[bind(name = "moved", code = 301)]
+    #[bind(name = "redirect", code = 302)]
+    Redirect,
+    #[bind(name = "error", code = 500..=599)] Error,
+    #[bind(name = "custom")] Custom { code: u16 },
+}
+
+#[test]
+fn lists_variants_and_bindings() {
+    assert_eq!(Status::VARIANTS, &["Ok", "Redirect", "Error", "Custom"]);
+    assert_eq!(Status::BINDINGS, &["code", "name"]);
+}
+
+#[test]
+fn table_holds_constant_values() {
+    assert_eq!(Status::TABLE, &[
+        ("Ok", &[("code", "200"), ("name", "ok")][..]),
+        ("Redirect", &[("code", "301"), ("name", "moved")][..]),
+        ("Redirect", &[("code", "302"), ("name", "redirect")][..]),
+        ("Error", &[("code", "500..=599"), ("name", "error")][..]),
+        ("Custom", &[("name", "custom")][..]),
+    ]);
+}
+
+fn binding_values<T: Bindable>(binding: &str) -> Vec<&'static str> {
+    T::TABLE.iter()
+        .flat_map(|(_, values)| values.iter())
+        .filter(|(name, _)| *name == binding)
+        .map(|(_, value)| *value)
+        .collect()
+}
+
+#[test]
+fn generic_tooling() {
+    #[derive(Bind)]
+    enum Empty {}
+
+    assert_eq!(binding_values::<Status>("name"), vec!["ok", "moved", "redirect", "error", "custom"]);
+    assert!(Empty::VARIANTS.is_empty());
+    assert!(binding_values::<Empty>("name").is_empty());
+}
+
+#[test]
+fn table_writes_patterns_from_literals() {
+    #[derive(Bind)]
+    #[allow(dead_code)]
+    enum Grade {
+        #[bind(score = 90..=100)] A,
+        #[bind(score = 0 | 1)] Zero,
+        #[bind(score = -10..0)] Penalty,
+    }
+
+    assert_eq!(Grade::TABLE, &[
+        ("A", &[("score", "90..=100")][..]),
+        ("Zero", &[("score", "0 | 1")][..]),
+        ("Penalty", &[("score", "-10..0")][..]),
+    ]);
+}
@@ -0,0 +1,66 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq)]
+#[query(fn is_prod_data(&self) -> bool, return = Bool(data_realm = "prod"))]
+#[query(fn is_in_realm(&self, realm: &str) -> bool, return = Bool(data_realm = realm))]
+#[query(fn is_staged(&self) -> bool, return = Bool(push_stage))]
+#[query(fn has_realm(data_realm: &str) -> bool, return = Any)]
+#[query(fn count_realm(data_realm: &str) -> usize, return = Count)]
+enum Environment {
+    #[bind(data_realm = "prod", push_stage = "prod")] Prod,
+    #[bind(data_realm = "prod", push_stage = "canary")] Canary,
+    #[bind(data_realm = "nonprod", push_stage = "autopush")] Autopush,
+    #[bind(data_realm = "local")] Local,
+}
+use Environment::*;
+
+#[test]
+fn bool_with_pattern() {
+    assert!(Prod.is_prod_data());
+    assert!(Canary.is_prod_data());
+    assert!(!Autopush.is_prod_data());
+    assert!(!Local.is_prod_data());
+}
+
+#[test]
+fn bool_with_argument() {
+    assert!(Prod.is_in_realm("prod"));
+    assert!(!Prod.is_in_realm("nonprod"));
+    assert!(Local.is_in_realm("local"));
+}
+
+#[test]
+fn bool_has_binding() {
+    assert!(Prod.is_staged());
+    assert!(Autopush.is_staged());
+    assert!(!Local.is_staged());
+}
+
+#[test]
+fn any_and_count() {
+    assert!(Environment::has_realm("prod"));
+    assert!(Environment::has_realm("local"));
+    assert!(!Environment::has_realm("test"));
+
+    assert_eq!(Environment::count_realm("prod"), 2);
+    assert_eq!(Environment::count_realm("nonprod"), 1);
+    assert_eq!(Environment::count_realm("test"), 0);
+}
+
+#[test]
+fn bool_borrows_fields() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn has_name(&self, n: &str) -> bool, return = Bool(name = n))]
+    #[query(fn has_arity(&self, a: usize) -> bool, return = Bool(arity = a))]
+    enum Item {
+        Function { name: String, arity: usize },
+        #[bind(name = "main".to_string())] Main,
+    }
+
+    let function = Item::Function { name: "parse".to_string(), arity: 2 };
+    assert!(function.has_name("parse"));
+    assert!(!function.has_name("main"));
+    assert!(Item::Main.has_name("main"));
+    assert!(function.has_arity(2));
+    assert!(!Item::Main.has_arity(0));
+}
@@ -0,0 +1,39 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq)]
+#[query(fn name(&self) -> Option<&String>)]
+#[query(fn label(&self) -> &str, return = Strict(label))]
+#[query(fn names(&self) -> Vec<&String>, return = Vec(name))]
+#[query(fn name_and_arity(&self) -> Option<(&String, usize)>, return = Option(name, arity))]
+enum Declaration {
+    #[bind(label = name)]
+    Function { name: String, params: Vec<String> },
+    #[bind(name = struct_name, label = struct_name, arity = fields.len())]
+    Struct { struct_name: String, fields: Vec<String> },
+    #[bind(label = "marker")]
+    Marker,
+}
+
+#[test]
+fn borrow_fields() {
+    let function = Declaration::Function { name: "main".to_string(), params: vec![] };
+    let structure = Declaration::Struct { struct_name: "Point".to_string(), fields: vec!["x".to_string(), "y".to_string()] };
+
+    assert_eq!(function.name(), Some(&"main".to_string()));
+    assert_eq!(structure.name(), Some(&"Point".to_string()));
+    assert_eq!(Declaration::Marker.name(), None);
+
+    assert_eq!(function.label(), "main");
+    assert_eq!(structure.label(), "Point");
+    assert_eq!(Declaration::Marker.label(), "marker");
+    assert_eq!(structure.names(), vec!["Point"]);
+    assert!(Declaration::Marker.names().is_empty());
+}
+
+#[test]
+fn expr_bindings_stay_owned() {
+    let structure = Declaration::Struct { struct_name: "Point".to_string(), fields: vec!["x".to_string(), "y".to_string()] };
+
+    assert_eq!(structure.name_and_arity(), Some((&"Point".to_string(), 2)));
+    assert_eq!(Declaration::Marker.name_and_arity(), None);
+}
@@ -0,0 +1,42 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq)]
+#[query(fn name_mut(&mut self) -> Option<&mut String>, return = Option(name))]
+#[query(fn params_mut(&mut self) -> &mut Vec<String>, return = Strict(params))]
+enum Declaration {
+    Function { name: String, params: Vec<String> },
+    #[bind(name = struct_name, params = fields)]
+    Struct { struct_name: String, fields: Vec<String> },
+    #[bind(name = _0, params = _1)]
+    Const(String, Vec<String>),
+}
+
+#[test]
+fn mutate_fields_through_bindings() {
+    let mut function = Declaration::Function { name: "main".to_string(), params: vec![] };
+    let mut structure = Declaration::Struct { struct_name: "Point".to_string(), fields: vec![] };
+    let mut constant = Declaration::Const("MAX".to_string(), vec![]);
+
+    function.name_mut().unwrap().push_str("_v2");
+    structure.name_mut().unwrap().push_str("3D");
+    constant.name_mut().unwrap().make_ascii_lowercase();
+    structure.params_mut().push("x".to_string());
+
+    assert_eq!(function, Declaration::Function { name: "main_v2".to_string(), params: vec![] });
+    assert_eq!(structure, Declaration::Struct { struct_name: "Point3D".to_string(), fields: vec!["x".to_string()] });
+    assert_eq!(constant, Declaration::Const("max".to_string(), vec![]));
+}
+
+#[test]
+fn copy_outputs_from_mut_self() {
+    #[derive(Bind, Debug, PartialEq, Clone, Copy)]
+    #[query(fn count(&mut self) -> Option<u32>)]
+    enum Counter {
+        Active { count: u32 },
+        #[bind(count = 0)]
+        Idle,
+    }
+
+    assert_eq!(Counter::Active { count: 3 }.count(), Some(3));
+    assert_eq!(Counter::Idle.count(), Some(0));
+}
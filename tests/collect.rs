@@ -0,0 +1,52 @@
+use std::collections::{BTreeSet, HashSet};
+
+use enum_bind::Bind;
+
+#[test]
+fn collect_into_set() {
+    #[derive(Bind, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    #[query(fn by_data_realm(data_realm: &str) -> BTreeSet<Self>, return = Collect)]
+    enum Environment {
+        #[bind(data_realm = "prod")] Prod,
+        #[bind(data_realm = "prod")] Canary,
+        #[bind(data_realm = "nonprod")] Autopush,
+    }
+    use Environment::*;
+
+    assert_eq!(Environment::by_data_realm("prod"), BTreeSet::from([Prod, Canary]));
+    assert_eq!(Environment::by_data_realm("test"), BTreeSet::new());
+}
+
+#[test]
+fn collect_removes_alias_duplicates() {
+    #[derive(Bind, Debug, PartialEq, Eq, Hash)]
+    #[query(fn by_alias(alias: &str) -> HashSet<Self>, return = Collect)]
+    #[query(fn kinds(&self) -> HashSet<&'static str>, return = Collect(kind))]
+    enum Declaration {
+        #[bind(alias = "fn", kind = "function")]
+        #[bind(alias = "func", kind = "function")]
+        #[bind(alias = "func", kind = "procedure")]
+        Function,
+        #[bind(alias = "struct", kind = "struct")]
+        Struct,
+    }
+    use Declaration::*;
+
+    assert_eq!(Declaration::by_alias("func"), HashSet::from([Function]));
+    assert_eq!(Function.kinds(), HashSet::from(["function", "procedure"]));
+    assert_eq!(Struct.kinds(), HashSet::from(["struct"]));
+}
+
+#[test]
+fn collect_into_boxed_slice() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn create(x: i32) -> Box<[Self]>, return = Collect)]
+    enum Enum {
+        #[bind(x = 1)] Alpha,
+        Beta { x: i32 },
+    }
+    use Enum::*;
+
+    assert_eq!(Enum::create(1), vec![Alpha, Beta { x: 1 }].into_boxed_slice());
+    assert_eq!(Enum::create(2), vec![Beta { x: 2 }].into_boxed_slice());
+}
@@ -0,0 +1,41 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq, Clone, Copy)]
+#[bind_default(region = "us", tier = !)]
+#[query(fn region(&self) -> &'static str, return = Strict)]
+#[query(fn tier(&self) -> Option<&'static str>)]
+#[query(fn in_region(region: &str) -> Vec<Self>, return = Vec)]
+enum Environment {
+    #[bind(data_realm = "prod")] Prod,
+    #[bind(data_realm = "prod", region = "eu", tier = "gold")] ProdEu,
+    Autopush,
+}
+
+#[test]
+fn defaults_are_inherited() {
+    assert_eq!(Environment::Prod.region(), "us");
+    assert_eq!(Environment::Autopush.region(), "us");
+    assert_eq!(Environment::Prod.tier(), None);
+    assert_eq!(Environment::in_region("us"), vec![Environment::Prod, Environment::Autopush]);
+}
+
+#[test]
+fn variants_override_defaults() {
+    assert_eq!(Environment::ProdEu.region(), "eu");
+    assert_eq!(Environment::ProdEu.tier(), Some("gold"));
+    assert_eq!(Environment::in_region("eu"), vec![Environment::ProdEu]);
+}
+
+#[test]
+fn fields_override_defaults() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[bind_default(name = "anonymous")]
+    #[query(fn name(&self) -> &'static str, return = Strict)]
+    enum User {
+        Guest,
+        Named { name: &'static str },
+    }
+
+    assert_eq!(User::Guest.name(), "anonymous");
+    assert_eq!(User::Named { name: "ada" }.name(), "ada");
+}
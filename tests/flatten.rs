@@ -0,0 +1,125 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq, Clone, Copy)]
+#[query(fn kind(&self) -> &'static str, return = Strict)]
+#[query(fn arity(&self) -> Option<usize>)]
+#[query(fn by_kind(kind: &str) -> Option<Self>)]
+#[query(fn all_by_kind(kind: &str) -> Vec<Self>, return = Vec)]
+#[query(fn is_kind(&self, expected: &str) -> bool, return = Bool(kind = expected))]
+enum ItemDecl {
+    #[bind(kind = "fn", arity = 0)] Function,
+    #[bind(kind = "struct")] Struct,
+}
+
+#[derive(Bind, Debug, PartialEq, Clone, Copy)]
+#[query(fn kind(&self) -> &'static str, return = Strict)]
+#[query(fn arity(&self) -> Option<usize>)]
+#[query(fn by_kind(kind: &str) -> Option<Self>)]
+#[query(fn all_by_kind(kind: &str) -> Vec<Self>, return = Vec)]
+#[query(fn is_kind(&self, expected: &str) -> bool, return = Bool(kind = expected))]
+enum Decl {
+    #[bind(flatten = _0)] Item(ItemDecl),
+    #[bind(kind = "const")] Const,
+}
+
+#[test]
+fn accessors_forward_to_nested_enum() {
+    assert_eq!(Decl::Item(ItemDecl::Function).kind(), "fn");
+    assert_eq!(Decl::Const.kind(), "const");
+    assert_eq!(Decl::Item(ItemDecl::Function).arity(), Some(0));
+    assert_eq!(Decl::Item(ItemDecl::Struct).arity(), None);
+    assert!(Decl::Item(ItemDecl::Struct).is_kind("struct"));
+    assert!(!Decl::Const.is_kind("struct"));
+}
+
+#[test]
+fn lookups_wrap_nested_results() {
+    assert_eq!(Decl::by_kind("struct"), Some(Decl::Item(ItemDecl::Struct)));
+    assert_eq!(Decl::by_kind("const"), Some(Decl::Const));
+    assert_eq!(Decl::by_kind("enum"), None);
+    assert_eq!(Decl::all_by_kind("fn"), vec![Decl::Item(ItemDecl::Function)]);
+}
+
+#[test]
+fn recursive_flatten() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn name(&self) -> &'static str, return = Strict)]
+    #[query(fn by_name(name: &str) -> Option<Self>)]
+    enum Expr {
+        #[bind(name = "literal")] Literal,
+        #[bind(flatten = _0)] Paren(Box<Self>),
+    }
+
+    assert_eq!(Expr::Paren(Box::new(Expr::Paren(Box::new(Expr::Literal)))).name(), "literal");
+    // Lookups skip the recursive variant, so they never find a value nested inside it
+    assert_eq!(Expr::by_name("literal"), Some(Expr::Literal));
+    assert_eq!(Expr::by_name("paren"), None);
+}
+
+#[test]
+fn nested_lookup_runs_once() {
+    use std::cell::Cell;
+
+    thread_local! {
+        static LOOKUPS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Tag(String);
+
+    impl Tag {
+        fn by_name(name: String) -> Option<Self> {
+            LOOKUPS.with(|lookups| lookups.set(lookups.get() + 1));
+            name.starts_with('#').then_some(Tag(name))
+        }
+
+        fn into_name(self) -> Result<String, Self> {
+            if self.0.len() > 1 { Ok(self.0) } else { Err(self) }
+        }
+    }
+
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn by_name(name: String) -> Option<Self>)]
+    #[query(fn into_name(self) -> Result<String, Self>, return = Result(name, Self))]
+    enum Token {
+        #[bind(name = !)] Eof,
+        #[bind(flatten = _0)] Tag(Tag),
+    }
+
+    assert_eq!(Token::by_name("#rust".to_string()), Some(Token::Tag(Tag("#rust".to_string()))));
+    assert_eq!(LOOKUPS.with(Cell::get), 1);
+    assert_eq!(Token::by_name("rust".to_string()), None);
+    assert_eq!(LOOKUPS.with(Cell::get), 2);
+    assert_eq!(Token::Eof.into_name(), Err(Token::Eof));
+
+    assert_eq!(Token::Tag(Tag("#rust".to_string())).into_name(), Ok("#rust".to_string()));
+    assert_eq!(Token::Tag(Tag("#".to_string())).into_name(), Err(Token::Tag(Tag("#".to_string()))));
+}
+
+#[test]
+fn nested_errors_are_passed_on() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn by_kind(kind: &str) -> Result<Option<Self>, ItemQueryError>, return = Single)]
+    #[query(fn create(kind: &str) -> Result<Self, ItemQueryError>, return = Result)]
+    enum Item {
+        #[bind(kind = "fn")] Function,
+        #[bind(kind = k if k.starts_with('f'))] Method,
+    }
+
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn by_kind(kind: &str) -> Result<Option<Self>, DeclQueryError>, return = Single)]
+    #[query(fn create(kind: &str) -> Result<Self, DeclQueryError>, return = Result)]
+    enum Decl {
+        #[bind(kind = "const")] Const,
+        #[bind(flatten = _0)] Item(Item),
+    }
+
+    assert_eq!(Decl::by_kind("fn"), Err(enum_bind::QueryError::Ambiguous {
+        query: "by_kind",
+        inputs: r#""fn""#.to_string(),
+        variants: vec!["Function", "Method"],
+    }));
+    assert_eq!(Decl::by_kind("const"), Ok(Some(Decl::Const)));
+    assert_eq!(Decl::create("fn"), Ok(Decl::Item(Item::Function)));
+    assert_eq!(Decl::create("enum"), Err(enum_bind::QueryError::NoMatch { query: "create", inputs: r#""enum""#.to_string() }));
+}
@@ -0,0 +1,56 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq)]
+#[query(fn from_number(n: i32) -> Option<Self>)]
+#[query(fn count_number(n: i32) -> usize, return = Count)]
+#[query(fn signs(n: i32) -> Vec<&'static str>, return = Vec(sign))]
+#[query(fn find(n: Option<i32>) -> Vec<Self>, return = Vec, optional_args)]
+enum Sign {
+    #[bind(n = x if x > 0, sign = "positive")] Positive,
+    #[bind(n = x if x < 0, sign = "negative")] Negative,
+    #[bind(n = 0, sign = "zero")] Zero,
+}
+
+#[test]
+fn guards_select_cases() {
+    assert_eq!(Sign::from_number(5), Some(Sign::Positive));
+    assert_eq!(Sign::from_number(-5), Some(Sign::Negative));
+    assert_eq!(Sign::from_number(0), Some(Sign::Zero));
+    assert_eq!(Sign::count_number(7), 1);
+    assert_eq!(Sign::signs(-1), vec!["negative"]);
+    assert_eq!(Sign::find(Some(3)), vec![Sign::Positive]);
+    assert_eq!(Sign::find(None), vec![Sign::Positive, Sign::Negative, Sign::Zero]);
+}
+
+#[test]
+fn failed_guards_fall_through() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn parse(name: &'static str) -> Option<Self>)]
+    #[query(fn find(name: Option<&'static str>) -> Vec<Self>, return = Vec, optional_args)]
+    enum Table {
+        #[bind(name = s if s.starts_with("tmp_"))]
+        Temporary { s: &'static str },
+        #[bind(name = s)]
+        Permanent { s: &'static str },
+    }
+
+    assert_eq!(Table::parse("tmp_users"), Some(Table::Temporary { s: "tmp_users" }));
+    assert_eq!(Table::parse("users"), Some(Table::Permanent { s: "users" }));
+    assert_eq!(Table::find(Some("users")), vec![Table::Permanent { s: "users" }]);
+    assert_eq!(Table::find(None), vec![]);
+}
+
+#[test]
+fn guards_on_owned_arguments() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn parse(name: String) -> Option<Self>)]
+    enum Table {
+        #[bind(name = s if s.starts_with("tmp_"))]
+        Temporary { s: String },
+        #[bind(name = s)]
+        Permanent { s: String },
+    }
+
+    assert_eq!(Table::parse("tmp_users".to_string()), Some(Table::Temporary { s: "tmp_users".to_string() }));
+    assert_eq!(Table::parse("users".to_string()), Some(Table::Permanent { s: "users".to_string() }));
+}
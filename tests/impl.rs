@@ -0,0 +1,47 @@
+use std::str::FromStr;
+
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq, Clone, Copy)]
+#[bind_impl(FromStr = name, Display = name, TryFrom<u8> = code, From<Self> for &'static str = name, PartialEq<&str> = name)]
+enum Environment {
+    #[bind(name = "prod", code = 1)] Prod,
+    #[bind(name = "canary", code = 2)] Canary,
+    #[bind(name = "autopush", code = 3)] Autopush,
+}
+
+#[test]
+fn from_str_and_display() {
+    assert_eq!(Environment::from_str("canary"), Ok(Environment::Canary));
+    assert_eq!("prod".parse::<Environment>(), Ok(Environment::Prod));
+    assert_eq!(
+        "local".parse::<Environment>(),
+        Err(EnvironmentQueryError::NoMatch { query: "from_str", inputs: r#""local""#.to_string() }),
+    );
+    assert_eq!(Environment::Autopush.to_string(), "autopush");
+}
+
+#[test]
+fn conversions() {
+    assert_eq!(Environment::try_from(2u8), Ok(Environment::Canary));
+    assert!(Environment::try_from(9u8).is_err());
+    let name: &'static str = Environment::Prod.into();
+    assert_eq!(name, "prod");
+    assert_eq!(Environment::Canary, "canary");
+    assert_ne!(Environment::Canary, "prod");
+}
+
+#[test]
+fn generic_enum() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[bind_impl(Display = name, From<Self> for &'static str = name, PartialEq<&str> = name)]
+    enum Slot<T> {
+        #[bind(name = "empty")] Empty,
+        #[bind(name = "full")] Full(T),
+    }
+
+    assert_eq!(Slot::Full(3).to_string(), "full");
+    let name: &'static str = Slot::<u8>::Empty.into();
+    assert_eq!(name, "empty");
+    assert_eq!(Slot::Full("x"), "full");
+}
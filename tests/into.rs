@@ -0,0 +1,37 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq)]
+#[query(fn into_payload(self) -> Option<Vec<u8>>, return = Option(payload))]
+#[query(fn into_name(self) -> Result<String, Self>, return = Result(name, Self))]
+#[query(fn into_inner(self) -> Box<Message>, return = Unwrap(inner))]
+enum Message {
+    Data { name: String, payload: Vec<u8> },
+    #[bind(payload = _1, name = _0)]
+    Raw(String, Vec<u8>),
+    #[bind(inner = _0)]
+    Wrapped(Box<Message>),
+    Empty,
+}
+
+#[test]
+fn into_moves_fields_out() {
+    let data = Message::Data { name: "data".to_string(), payload: vec![1, 2] };
+    let raw = Message::Raw("raw".to_string(), vec![3]);
+
+    assert_eq!(data.into_payload(), Some(vec![1, 2]));
+    assert_eq!(raw.into_payload(), Some(vec![3]));
+    assert_eq!(Message::Empty.into_payload(), None);
+
+    let wrapped = Message::Wrapped(Box::new(Message::Empty));
+    assert_eq!(wrapped.into_inner(), Box::new(Message::Empty));
+}
+
+#[test]
+fn into_gives_back_self() {
+    let data = Message::Data { name: "data".to_string(), payload: vec![] };
+    let raw = Message::Raw("raw".to_string(), vec![]);
+
+    assert_eq!(data.into_name(), Ok("data".to_string()));
+    assert_eq!(raw.into_name(), Ok("raw".to_string()));
+    assert_eq!(Message::Empty.into_name(), Err(Message::Empty));
+}
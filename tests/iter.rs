@@ -0,0 +1,75 @@
+use enum_bind::Bind;
+
+#[test]
+fn iter_self_for_column() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn by_data_realm(data_realm: &str) -> impl Iterator<Item = Self>, return = Iter)]
+    enum Environment {
+        #[bind(data_realm = "prod")] Prod,
+        #[bind(data_realm = "prod")] Canary,
+        #[bind(data_realm = "nonprod")] Autopush,
+        #[bind(data_realm = "prod")] StagingWithProdData,
+    }
+    use Environment::*;
+
+    let mut prod = Environment::by_data_realm("prod");
+    assert_eq!(prod.next(), Some(Prod));
+    assert_eq!(prod.next(), Some(Canary));
+    assert_eq!(prod.next(), Some(StagingWithProdData));
+    assert_eq!(prod.next(), None);
+
+    assert_eq!(Environment::by_data_realm("nonprod").collect::<Vec<_>>(), vec![Autopush]);
+    assert_eq!(Environment::by_data_realm("test").count(), 0);
+}
+
+#[test]
+fn iter_with_capture() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn create(x: i32) -> impl Iterator<Item = Self>, return = Iter)]
+    enum Enum {
+        #[bind(x = 1)] Alpha,
+        Beta { x: i32 },
+        #[bind(x = _0)] Gamma(i32),
+    }
+    use Enum::*;
+
+    assert_eq!(Enum::create(1).take(2).collect::<Vec<_>>(), vec![Alpha, Beta { x: 1 }]);
+    assert_eq!(Enum::create(2).collect::<Vec<_>>(), vec![Beta { x: 2 }, Gamma(2)]);
+}
+
+#[test]
+fn iter_with_owned_arguments() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn by_label(label: String) -> impl Iterator<Item = Self>, return = Iter)]
+    enum Task {
+        #[bind(label = l if l.starts_with("build"))] Build,
+        Named { label: String },
+        #[bind(label = l if l.len() > 3)] Long,
+    }
+    use Task::*;
+
+    assert_eq!(
+        Task::by_label("build-all".to_string()).collect::<Vec<_>>(),
+        vec![Build, Named { label: "build-all".to_string() }, Long],
+    );
+    assert_eq!(Task::by_label("ci".to_string()).collect::<Vec<_>>(), vec![Named { label: "ci".to_string() }]);
+}
+
+#[test]
+fn iter_column_for_self() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn stage(&self) -> impl Iterator<Item = &'static str> + '_, return = Iter)]
+    enum Environment {
+        #[bind(stage = "staging")]
+        #[bind(stage = "canary")]
+        Canary,
+        #[bind(stage = "prod")]
+        Prod,
+        Local,
+    }
+    use Environment::*;
+
+    assert_eq!(Canary.stage().collect::<Vec<_>>(), vec!["staging", "canary"]);
+    assert_eq!(Prod.stage().collect::<Vec<_>>(), vec!["prod"]);
+    assert_eq!(Local.stage().next(), None);
+}
@@ -0,0 +1,51 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq, Clone, Copy)]
+#[query(fn find(data_realm: Option<&str>, push_stage: Option<&str>) -> Vec<Self>, return = Vec, optional_args)]
+#[query(fn stages(data_realm: Option<&str>) -> Vec<&'static str>, return = Vec(push_stage), optional_args)]
+#[query(fn count(push_stage: Option<&str>) -> usize, return = Count, optional_args)]
+enum Environment {
+    #[bind(data_realm = "prod", push_stage = "prod")] Prod,
+    #[bind(data_realm = "prod", push_stage = "canary")] Canary,
+    #[bind(data_realm = "prod", push_stage = "staging")] StagingWithProdData,
+    #[bind(data_realm = "nonprod", push_stage = "staging")] StagingWithTestData,
+    #[bind(data_realm = "nonprod", push_stage = "autopush")] Autopush,
+    #[bind(data_realm = "test", push_stage = !)] IntegrationTests,
+}
+
+#[test]
+fn none_matches_any_case() {
+    use Environment::*;
+    assert_eq!(Environment::find(None, None), vec![Prod, Canary, StagingWithProdData, StagingWithTestData, Autopush, IntegrationTests]);
+    assert_eq!(Environment::find(Some("prod"), None), vec![Prod, Canary, StagingWithProdData]);
+    assert_eq!(Environment::find(None, Some("staging")), vec![StagingWithProdData, StagingWithTestData]);
+    assert_eq!(Environment::find(Some("nonprod"), Some("staging")), vec![StagingWithTestData]);
+    assert_eq!(Environment::find(Some("test"), Some("staging")), vec![]);
+    assert_eq!(Environment::count(None), 6);
+    assert_eq!(Environment::count(Some("staging")), 2);
+}
+
+#[test]
+fn optional_args_with_projection() {
+    assert_eq!(Environment::stages(Some("nonprod")), vec!["staging", "autopush"]);
+    assert_eq!(Environment::stages(None), vec!["prod", "canary", "staging", "staging", "autopush"]);
+}
+
+#[test]
+fn optional_field_bindings() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn find(kind: Option<&str>, name: Option<&'static str>) -> Vec<Self>, return = Vec, optional_args)]
+    enum Declaration {
+        #[bind(kind = "fn")]
+        Function { name: &'static str },
+        #[bind(kind = "struct")]
+        Struct { name: &'static str },
+        #[bind(kind = "mod")]
+        Module,
+    }
+
+    use Declaration::*;
+    assert_eq!(Declaration::find(None, Some("foo")), vec![Function { name: "foo" }, Struct { name: "foo" }]);
+    assert_eq!(Declaration::find(Some("fn"), Some("foo")), vec![Function { name: "foo" }]);
+    assert_eq!(Declaration::find(None, None), vec![Module]);
+}
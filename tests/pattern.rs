@@ -0,0 +1,45 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq, Clone, Copy)]
+#[query(fn from_status(status: u16) -> Option<Self>)]
+#[query(fn has_status(&self, code: u16) -> bool, return = Bool(status = code))]
+#[query(fn is_not_found(&self) -> bool, return = Bool(status = 404))]
+enum StatusClass {
+    #[bind(status = 100..=199)] Informational,
+    #[bind(status = 200..=299)] Success,
+    #[bind(status = 300..=399)] Redirection,
+    #[bind(status = 400..=499)] ClientError,
+    #[bind(status = 500..=599)] ServerError,
+}
+
+#[test]
+fn range_bindings() {
+    assert_eq!(StatusClass::from_status(204), Some(StatusClass::Success));
+    assert_eq!(StatusClass::from_status(404), Some(StatusClass::ClientError));
+    assert_eq!(StatusClass::from_status(42), None);
+
+    assert!(StatusClass::Success.has_status(200));
+    assert!(!StatusClass::Success.has_status(500));
+    assert!(StatusClass::ClientError.is_not_found());
+    assert!(!StatusClass::ServerError.is_not_found());
+}
+
+#[test]
+fn or_pattern_bindings() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn by_keyword(keyword: &str) -> Option<Self>)]
+    #[query(fn keyword(&self) -> &'static str, return = Strict(name))]
+    #[query(fn count_keyword(keyword: &str) -> usize, return = Count)]
+    #[bind_query(KindQuery)]
+    enum Kind {
+        #[bind(keyword = "fn" | "function", name = "function")] Function,
+        #[bind(keyword = "struct", name = "struct")] Struct,
+    }
+
+    assert_eq!(Kind::by_keyword("fn"), Some(Kind::Function));
+    assert_eq!(Kind::by_keyword("function"), Some(Kind::Function));
+    assert_eq!(Kind::by_keyword("struct"), Some(Kind::Struct));
+    assert_eq!(Kind::Function.keyword(), "function");
+    assert_eq!(Kind::count_keyword("fn"), 1);
+    assert_eq!(Kind::query().keyword("function").first(), Some(Kind::Function));
+}
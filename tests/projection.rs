@@ -0,0 +1,44 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq)]
+#[query(fn stages_for_realm(data_realm: &str) -> Vec<&'static str>, return = Vec(push_stage))]
+#[query(fn realm_for_stage(push_stage: &str) -> Option<&'static str>, return = Option(data_realm))]
+#[query(fn count_for_stage(push_stage: &str) -> usize, return = Count)]
+#[allow(dead_code)]
+enum Environment {
+    #[bind(data_realm = "prod", push_stage = "prod")] Prod,
+    #[bind(data_realm = "prod", push_stage = "canary")] Canary,
+    #[bind(data_realm = "prod", push_stage = "staging")] StagingWithProdData,
+    #[bind(data_realm = "nonprod", push_stage = "staging")] StagingWithTestData,
+    #[bind(data_realm = "nonprod", push_stage = "autopush")] Autopush,
+    #[bind(data_realm = "test", push_stage = !)] IntegrationTests,
+}
+
+#[test]
+fn project_binding_to_binding() {
+    assert_eq!(Environment::stages_for_realm("prod"), vec!["prod", "canary", "staging"]);
+    assert_eq!(Environment::stages_for_realm("nonprod"), vec!["staging", "autopush"]);
+    assert_eq!(Environment::stages_for_realm("test"), Vec::<&str>::new());
+
+    assert_eq!(Environment::realm_for_stage("canary"), Some("prod"));
+    assert_eq!(Environment::realm_for_stage("staging"), Some("prod"));
+    assert_eq!(Environment::realm_for_stage("local"), None);
+    assert_eq!(Environment::count_for_stage("staging"), 2);
+}
+
+#[test]
+fn project_field_binding() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn name_of_kind(kind: &str, name: &'static str) -> Option<(&'static str, usize)>, return = Option(name, arity))]
+    #[allow(dead_code)]
+    enum Declaration {
+        #[bind(kind = "fn", arity = 0)]
+        Function { name: &'static str },
+        #[bind(kind = "struct", name = struct_name, arity = 2)]
+        Struct { struct_name: &'static str },
+    }
+
+    assert_eq!(Declaration::name_of_kind("fn", "main"), Some(("main", 0)));
+    assert_eq!(Declaration::name_of_kind("struct", "Point"), Some(("Point", 2)));
+    assert_eq!(Declaration::name_of_kind("enum", "E"), None);
+}
@@ -0,0 +1,61 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq, Clone, Copy)]
+#[bind_query(EnvironmentQuery)]
+#[query(fn query_all(data_realm: &str) -> Vec<Self>, return = Vec)]
+enum Environment {
+    #[bind(data_realm = "prod", push_stage = "prod")] Prod,
+    #[bind(data_realm = "prod", push_stage = "canary")] Canary,
+    #[bind(data_realm = "prod", push_stage = "staging")] StagingWithProdData,
+    #[bind(data_realm = "nonprod", push_stage = "staging")] StagingWithTestData,
+    #[bind(data_realm = "nonprod", push_stage = "autopush")] Autopush,
+    #[bind(data_realm = "test", push_stage = !)] IntegrationTests,
+}
+
+#[test]
+fn builder_terminals() {
+    use Environment::*;
+    assert_eq!(Environment::query().data_realm("prod").all(), vec![Prod, Canary, StagingWithProdData]);
+    assert_eq!(Environment::query().push_stage("staging").first(), Some(StagingWithProdData));
+    assert_eq!(Environment::query().data_realm("prod").push_stage("canary").single(), Ok(Some(Canary)));
+    assert_eq!(Environment::query().data_realm("local").first(), None);
+    assert_eq!(Environment::query().all().len(), 6);
+    assert_eq!(Environment::query_all("test"), vec![IntegrationTests]);
+}
+
+#[test]
+fn builder_single_is_ambiguous() {
+    assert_eq!(
+        Environment::query().push_stage("staging").single(),
+        Err(EnvironmentQueryError::Ambiguous {
+            query: "EnvironmentQuery::single",
+            inputs: r#"push_stage = "staging""#.to_string(),
+            variants: vec!["StagingWithProdData", "StagingWithTestData"],
+        }),
+    );
+}
+
+#[test]
+fn builder_takes_runtime_strings() {
+    use Environment::*;
+    let realm = String::from("nonprod");
+    let stage = String::from("staging");
+    assert_eq!(Environment::query().data_realm(&realm).all(), vec![StagingWithTestData, Autopush]);
+    assert_eq!(Environment::query().data_realm(&realm).push_stage(&stage).single(), Ok(Some(StagingWithTestData)));
+}
+
+#[test]
+fn builder_with_declared_types() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[bind_query(DeclarationQuery, name: &'static str)]
+    enum Declaration {
+        #[bind(kind = "fn")]
+        Function { name: &'static str },
+        #[bind(kind = "struct")]
+        Struct { name: &'static str },
+    }
+
+    assert_eq!(Declaration::query().kind("struct").name("Foo").first(), Some(Declaration::Struct { name: "Foo" }));
+    assert_eq!(Declaration::query().name("foo").all(), vec![Declaration::Function { name: "foo" }, Declaration::Struct { name: "foo" }]);
+    assert_eq!(Declaration::query().kind("fn").all(), vec![]);
+}
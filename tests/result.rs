@@ -0,0 +1,92 @@
+use enum_bind::Bind;
+
+#[test]
+fn result_self_for_column() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn create(kind: &'static str) -> Result<Self, DeclarationQueryError>, return = Result)]
+    enum Declaration {
+        #[bind(kind = "fn")]
+        Function,
+        #[bind(kind = "struct")]
+        Struct,
+    }
+    use Declaration::*;
+
+    assert_eq!(Declaration::create("fn"), Ok(Function));
+    assert_eq!(Declaration::create("struct"), Ok(Struct));
+    assert_eq!(Declaration::create("enum"), Err(DeclarationQueryError::NoMatch {
+        query: "create",
+        inputs: r#""enum""#.to_string(),
+    }));
+}
+
+#[test]
+fn result_column_for_self() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn name(&self) -> Result<&'static str, EnumQueryError>, return = Result)]
+    enum Enum {
+        #[bind(name = "alpha")]
+        Alpha,
+        Beta { name: &'static str },
+        Gamma,
+    }
+    use Enum::*;
+
+    assert_eq!(Alpha.name(), Ok("alpha"));
+    assert_eq!(Beta { name: "beta" }.name(), Ok("beta"));
+    assert_eq!(Gamma.name().unwrap_err().to_string(), r#"Query "name" has no result for Gamma"#);
+}
+
+#[test]
+fn result_with_error_type() {
+    #[derive(Debug, PartialEq)]
+    struct NotFound(String);
+
+    impl From<EnumQueryError> for NotFound {
+        fn from(error: EnumQueryError) -> Self {
+            NotFound(error.to_string())
+        }
+    }
+
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn by_a(a: i32) -> Result<Self, NotFound>, return = Result(_, NotFound))]
+    #[query(fn b(&self) -> Result<i32, NotFound>, return = Result(b, NotFound))]
+    enum Enum {
+        #[bind(a = 1, b = 10)]
+        Alpha,
+        #[bind(a = 2)]
+        Beta,
+    }
+
+    fn lookup(a: i32) -> Result<i32, NotFound> {
+        Enum::by_a(a)?.b()
+    }
+
+    assert_eq!(lookup(1), Ok(10));
+    assert_eq!(lookup(2), Err(NotFound(r#"Query "b" has no result for Beta"#.to_string())));
+    assert_eq!(lookup(3), Err(NotFound(r#"Query "by_a" has no result for 3"#.to_string())));
+}
+
+#[test]
+fn default_error_is_shared() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn by_code(code: u8) -> Result<Self, StatusQueryError>, return = Result)]
+    enum Status {
+        #[bind(code = 0)]
+        Ok,
+    }
+
+    // No query returns the error, but the name exists all the same
+    #[derive(Bind, Debug, PartialEq)]
+    #[allow(dead_code)]
+    enum Level {
+        #[bind(code = 0)]
+        Low,
+    }
+
+    fn failures() -> Vec<enum_bind::QueryError> {
+        vec![Status::by_code(1).unwrap_err(), LevelQueryError::NoMatch { query: "by_code", inputs: "1".to_string() }]
+    }
+
+    assert_eq!(failures()[0], failures()[1]);
+}
@@ -0,0 +1,92 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq, Clone, Copy)]
+#[bind_row(#[derive(Debug, PartialEq)] EnvironmentRow)]
+enum Environment {
+    #[bind(data_realm = "prod", push_stage = "prod", replicas = 3u8)] Prod,
+    #[bind(data_realm = "prod", push_stage = "canary", replicas = 1u8)] Canary,
+    #[bind(data_realm = "nonprod", push_stage = "autopush")] Autopush { replicas: u8 },
+    #[bind(data_realm = "local")] Local,
+}
+
+#[test]
+fn row_holds_all_bindings() {
+    assert_eq!(Environment::Prod.row(), EnvironmentRow {
+        data_realm: "prod",
+        push_stage: Some("prod"),
+        replicas: Some(3),
+    });
+    assert_eq!(Environment::Autopush { replicas: 2 }.row(), EnvironmentRow {
+        data_realm: "nonprod",
+        push_stage: Some("autopush"),
+        replicas: Some(2),
+    });
+    assert_eq!(Environment::Local.row(), EnvironmentRow {
+        data_realm: "local",
+        push_stage: None,
+        replicas: None,
+    });
+}
+
+#[test]
+fn from_row_round_trips() {
+    for environment in [Environment::Prod, Environment::Canary, Environment::Autopush { replicas: 5 }, Environment::Local] {
+        assert_eq!(Environment::from_row(&environment.row()), Some(environment));
+    }
+
+    assert_eq!(Environment::from_row(&EnvironmentRow {
+        data_realm: "prod",
+        push_stage: Some("prod"),
+        replicas: Some(4),
+    }), None);
+}
+
+#[test]
+fn row_with_declared_types() {
+    #[derive(Bind, Debug, PartialEq, Clone, Copy)]
+    #[bind_row(#[derive(Debug, PartialEq)] DeclarationRow, kind: &'static str)]
+    enum Declaration {
+        #[bind(kind = "fn", name = fn_name)]
+        Function { fn_name: &'static str },
+        #[bind(kind = "marker")]
+        Marker,
+    }
+
+    assert_eq!(Declaration::Function { fn_name: "main" }.row(), DeclarationRow {
+        fn_name: Some("main"),
+        kind: "fn",
+        name: Some("main"),
+    });
+    assert_eq!(Declaration::from_row(&Declaration::Marker.row()), Some(Declaration::Marker));
+}
+
+#[test]
+fn row_clones_fields() {
+    #[derive(Bind, Debug, PartialEq, Clone)]
+    #[bind_row(#[derive(Debug, PartialEq)] ServiceRow)]
+    enum Service {
+        #[bind(port = 443u16)]
+        Https { host: String, path: String },
+        Local { port: u16 },
+    }
+
+    let service = Service::Https { host: "example.com".to_string(), path: "/".to_string() };
+    assert_eq!(service.row(), ServiceRow { host: Some("example.com".to_string()), path: Some("/".to_string()), port: 443 });
+    assert_eq!(Service::from_row(&service.row()), Some(service));
+    assert_eq!(Service::Local { port: 80 }.row(), ServiceRow { host: None, path: None, port: 80 });
+}
+
+#[test]
+fn row_with_computed_bindings() {
+    #[derive(Bind, Debug, PartialEq, Clone, Copy)]
+    #[bind_row(#[derive(Debug, PartialEq)] TaskRow, label: String, weight: i32)]
+    enum Task {
+        #[bind(label = "build".to_string(), weight = 2 * 3)] Build,
+        #[bind(label = "test".to_string(), weight = 1)] Test,
+    }
+
+    assert_eq!(Task::Build.row(), TaskRow { label: "build".to_string(), weight: 6 });
+    assert_eq!(Task::from_row(&Task::Build.row()), Some(Task::Build));
+    assert_eq!(Task::from_row(&Task::Test.row()), Some(Task::Test));
+    assert_eq!(Task::from_row(&TaskRow { label: "build".to_string(), weight: 1 }), None);
+}
@@ -0,0 +1,51 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq, Clone, Copy)]
+#[bindings(data_realm: &'static str, push_stage: Option<&'static str>)]
+enum Environment {
+    #[bind(data_realm = "prod", push_stage = "prod")] Prod,
+    #[bind(data_realm = "prod", push_stage = "canary")] Canary,
+    #[bind(data_realm = "nonprod", push_stage = "staging")] Staging,
+    #[bind(data_realm = "test", push_stage = !)] IntegrationTests,
+}
+
+#[test]
+fn schema_accessors() {
+    assert_eq!(Environment::Canary.data_realm(), "prod");
+    assert_eq!(Environment::IntegrationTests.data_realm(), "test");
+    assert_eq!(Environment::Staging.push_stage(), Some("staging"));
+    assert_eq!(Environment::IntegrationTests.push_stage(), None);
+}
+
+#[test]
+fn schema_lookups() {
+    use Environment::*;
+    assert_eq!(Environment::by_data_realm("prod"), vec![Prod, Canary]);
+    assert_eq!(Environment::by_data_realm("local"), vec![]);
+    assert_eq!(Environment::by_push_stage("staging"), vec![Staging]);
+}
+
+#[test]
+fn schema_with_fields() {
+    #[derive(Bind, Debug, PartialEq, Clone, Copy)]
+    #[bindings(kind: &'static str, arity: Option<usize>)]
+    enum Declaration {
+        #[bind(kind = "fn")]
+        Function { arity: usize },
+        #[bind(kind = "struct")]
+        Struct,
+    }
+
+    assert_eq!(Declaration::Function { arity: 2 }.arity(), Some(2));
+    assert_eq!(Declaration::Struct.arity(), None);
+    assert_eq!(Declaration::by_kind("struct"), vec![Declaration::Struct]);
+    assert_eq!(Declaration::by_kind("fn"), vec![]);
+    assert_eq!(Declaration::by_arity(3), vec![Declaration::Function { arity: 3 }]);
+}
+
+#[test]
+fn schema_lookups_borrow_arguments() {
+    let config = String::from("nonprod");
+    assert_eq!(Environment::by_data_realm(&config), vec![Environment::Staging]);
+    assert_eq!(Environment::by_push_stage(config.as_str()), vec![]);
+}
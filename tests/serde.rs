@@ -0,0 +1,56 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq)]
+#[bind_serde(name)]
+enum Environment {
+    #[bind(name = "prod")] Prod,
+    #[bind(name = "canary")] Canary,
+    #[bind(name = "custom", port = 8080)]
+    Custom { host: String },
+}
+
+#[test]
+fn serialize_through_binding() {
+    assert_eq!(serde_json::to_string(&Environment::Prod).unwrap(), r#""prod""#);
+    assert_eq!(serde_json::to_string(&Environment::Canary).unwrap(), r#""canary""#);
+    assert_eq!(
+        serde_json::to_string(&Environment::Custom { host: "localhost".to_string() }).unwrap(),
+        r#"{"name":"custom","host":"localhost","port":8080}"#,
+    );
+}
+
+#[test]
+fn deserialize_through_binding() {
+    assert_eq!(serde_json::from_str::<Environment>(r#""canary""#).unwrap(), Environment::Canary);
+    assert_eq!(
+        serde_json::from_str::<Environment>(r#"{"name":"custom","port":8080,"host":"localhost"}"#).unwrap(),
+        Environment::Custom { host: "localhost".to_string() },
+    );
+    assert!(serde_json::from_str::<Environment>(r#""staging""#).is_err());
+    assert!(serde_json::from_str::<Environment>(r#"{"name":"custom"}"#).is_err());
+}
+
+#[test]
+fn deserialize_key_anywhere() {
+    assert_eq!(
+        serde_json::from_str::<Environment>(r#"{"host":"localhost","name":"custom"}"#).unwrap(),
+        Environment::Custom { host: "localhost".to_string() },
+    );
+    assert_eq!(serde_json::from_str::<Environment>(r#"{"name":"prod"}"#).unwrap(), Environment::Prod);
+    assert!(serde_json::from_str::<Environment>(r#"{"host":"localhost"}"#).is_err());
+}
+
+#[test]
+fn integer_keys() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[bind_serde(code)]
+    enum Status {
+        #[bind(code = 200u16)] Ok,
+        #[bind(code = 404u16)] NotFound,
+    }
+
+    assert_eq!(serde_json::to_string(&Status::NotFound).unwrap(), "404");
+    assert_eq!(serde_json::from_str::<Status>("200").unwrap(), Status::Ok);
+    assert!(serde_json::from_str::<Status>("500").is_err());
+    assert!(serde_json::from_str::<Status>(r#""ok""#).is_err());
+}
@@ -0,0 +1,54 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq, Clone, Copy)]
+#[query(pub data_realm, return = Strict)]
+#[query(push_stage)]
+#[query(port, return = Strict)]
+#[query(by push_stage)]
+#[query(by data_realm, return = Vec)]
+#[query(by port, return = Result)]
+enum Environment {
+    #[bind(data_realm = "prod", push_stage = "prod", port = 443u16)] Prod,
+    #[bind(data_realm = "prod", push_stage = "canary", port = 8443u16)] Canary,
+    #[bind(data_realm = "test", push_stage = !, port = 8080u16)] IntegrationTests,
+}
+
+#[test]
+fn shorthand_accessors() {
+    assert_eq!(Environment::Canary.data_realm(), "prod");
+    assert_eq!(Environment::Canary.push_stage(), Some("canary"));
+    assert_eq!(Environment::IntegrationTests.push_stage(), None);
+    let port: u16 = Environment::Prod.port();
+    assert_eq!(port, 443);
+}
+
+#[test]
+fn shorthand_lookups() {
+    assert_eq!(Environment::by_push_stage("canary"), Some(Environment::Canary));
+    assert_eq!(Environment::by_data_realm("prod"), vec![Environment::Prod, Environment::Canary]);
+    assert_eq!(Environment::by_port(8080), Ok(Environment::IntegrationTests));
+    assert!(Environment::by_port(80).is_err());
+}
+
+#[test]
+fn shorthand_lookups_borrow_arguments() {
+    let stage = String::from("canary");
+    assert_eq!(Environment::by_push_stage(&stage), Some(Environment::Canary));
+    assert_eq!(Environment::by_data_realm(stage.as_str()), vec![]);
+}
+
+#[test]
+fn shorthand_borrows_owned_fields() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(name)]
+    #[query(size, return = Strict)]
+    enum Source {
+        #[bind(size = 0usize)] Empty,
+        Named { name: String, size: usize },
+    }
+
+    let source = Source::Named { name: "config.toml".to_string(), size: 12 };
+    assert_eq!(source.name(), Some(&"config.toml".to_string()));
+    assert_eq!(source.size(), 12);
+    assert_eq!(Source::Empty.name(), None);
+}
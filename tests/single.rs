@@ -0,0 +1,68 @@
+use enum_bind::Bind;
+
+#[test]
+fn single_self_for_column() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn by_code(code: u8) -> Result<Option<Self>, StatusQueryError>, return = Single)]
+    enum Status {
+        #[bind(code = 0)]
+        Success,
+        #[bind(code = 1..=9)]
+        Failed,
+        #[bind(code = 2)]
+        Crashed,
+    }
+    use Status::*;
+
+    assert_eq!(Status::by_code(0), Ok(Some(Success)));
+    assert_eq!(Status::by_code(1), Ok(Some(Failed)));
+    assert_eq!(Status::by_code(10), Ok(None));
+    assert_eq!(Status::by_code(2), Err(StatusQueryError::Ambiguous {
+        query: "by_code",
+        inputs: "2".to_string(),
+        variants: vec!["Failed", "Crashed"],
+    }));
+}
+
+#[test]
+fn single_with_error_type() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn by_kind(kind: &str) -> Result<Option<Self>, LookupError>, return = Single(_, LookupError))]
+    enum Declaration {
+        #[bind(kind = "fn")]
+        #[bind(kind = "function")]
+        Function,
+        #[bind(kind = "struct")]
+        Struct,
+    }
+    use Declaration::*;
+
+    #[derive(Debug, PartialEq)]
+    struct LookupError(String);
+
+    impl From<DeclarationQueryError> for LookupError {
+        fn from(error: DeclarationQueryError) -> Self {
+            LookupError(error.to_string())
+        }
+    }
+
+    assert_eq!(Declaration::by_kind("fn"), Ok(Some(Function)));
+    assert_eq!(Declaration::by_kind("function"), Ok(Some(Function)));
+    assert_eq!(Declaration::by_kind("struct"), Ok(Some(Struct)));
+    assert_eq!(Declaration::by_kind("enum"), Ok(None));
+}
+
+#[test]
+fn single_column_for_self() {
+    #[derive(Bind, Debug, PartialEq)]
+    #[query(fn stage(&self) -> Result<Option<&'static str>, EnvironmentQueryError>, return = Single)]
+    enum Environment {
+        #[bind(stage = "prod")]
+        Prod,
+        Local,
+    }
+    use Environment::*;
+
+    assert_eq!(Prod.stage(), Ok(Some("prod")));
+    assert_eq!(Local.stage(), Ok(None));
+}
@@ -0,0 +1,50 @@
+use enum_bind::Bind;
+
+trait HasRealm: Sized {
+    fn data_realm(&self) -> &'static str;
+    fn by_realm(realm: &str) -> Vec<Self>;
+}
+
+#[derive(Bind, Debug, PartialEq)]
+#[query(impl HasRealm {
+    fn data_realm(&self) -> &'static str, return = Strict(realm);
+    fn by_realm(realm: &str) -> Vec<Self>, return = Vec;
+})]
+#[query(pub fn is_local(&self) -> bool, return = Bool(realm = "local"))]
+enum Storage {
+    #[bind(realm = "local")] Disk,
+    #[bind(realm = "local")] Memory,
+    #[bind(realm = "remote")] Bucket,
+}
+
+#[derive(Bind, Debug, PartialEq)]
+#[query(impl HasRealm {
+    fn data_realm(&self) -> &'static str, return = Strict(realm);
+    fn by_realm(realm: &str) -> Vec<Self>, return = Vec
+})]
+enum Cache {
+    #[bind(realm = "local")] Lru,
+    #[bind(realm = "remote")] Shared,
+}
+
+fn realms<T: HasRealm>(values: &[T]) -> Vec<&'static str> {
+    values.iter().map(HasRealm::data_realm).collect()
+}
+
+#[test]
+fn generic_code_uses_trait_impl() {
+    assert_eq!(realms(&[Storage::Bucket, Storage::Disk]), vec!["remote", "local"]);
+    assert_eq!(realms(&[Cache::Lru, Cache::Shared]), vec!["local", "remote"]);
+}
+
+#[test]
+fn trait_constructors() {
+    assert_eq!(Storage::by_realm("local"), vec![Storage::Disk, Storage::Memory]);
+    assert_eq!(<Cache as HasRealm>::by_realm("remote"), vec![Cache::Shared]);
+}
+
+#[test]
+fn inherent_queries_alongside_trait() {
+    assert!(Storage::Memory.is_local());
+    assert!(!Storage::Bucket.is_local());
+}
@@ -0,0 +1,32 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq)]
+#[query(fn realm_and_stage(&self) -> (&'static str, &'static str), return = Strict(data_realm, push_stage))]
+#[query(fn stage_and_tier(&self) -> Option<(&'static str, u8)>, return = Option(push_stage, tier))]
+#[query(fn realm_and_tier(&self) -> Result<(&'static str, u8), EnvironmentQueryError>, return = Result((data_realm, tier)))]
+enum Environment {
+    #[bind(data_realm = "prod", push_stage = "prod", tier = 1)] Prod,
+    #[bind(data_realm = "prod", push_stage = "canary")] Canary,
+    #[bind(data_realm = "nonprod", push_stage = "autopush")] Autopush { tier: u8 },
+}
+use Environment::*;
+
+#[test]
+fn strict_tuple() {
+    assert_eq!(Prod.realm_and_stage(), ("prod", "prod"));
+    assert_eq!(Canary.realm_and_stage(), ("prod", "canary"));
+    assert_eq!(Autopush { tier: 3 }.realm_and_stage(), ("nonprod", "autopush"));
+}
+
+#[test]
+fn option_tuple_requires_every_binding() {
+    assert_eq!(Prod.stage_and_tier(), Some(("prod", 1)));
+    assert_eq!(Canary.stage_and_tier(), None);
+    assert_eq!(Autopush { tier: 3 }.stage_and_tier(), Some(("autopush", 3)));
+}
+
+#[test]
+fn result_tuple() {
+    assert_eq!(Prod.realm_and_tier(), Ok(("prod", 1)));
+    assert!(Canary.realm_and_tier().is_err());
+}
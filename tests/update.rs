@@ -0,0 +1,47 @@
+use enum_bind::Bind;
+
+#[derive(Bind, Debug, PartialEq)]
+#[query(fn with_name(self, name: String) -> Self, return = Update(name))]
+#[query(fn set_name(&mut self, name: String) -> bool, return = Set(name))]
+enum Declaration {
+    Function { name: String, params: Vec<String> },
+    #[bind(name = struct_name)]
+    Struct { struct_name: String },
+    #[bind(name = _0)]
+    Const(String, i64),
+    #[bind(name = "marker".to_string())]
+    Marker,
+    #[bind(name = !)]
+    Anonymous { params: Vec<String> },
+}
+
+#[test]
+fn update_rebuilds_variant() {
+    let function = Declaration::Function { name: "main".to_string(), params: vec!["argc".to_string()] };
+    assert_eq!(function.with_name("start".to_string()), Declaration::Function {
+        name: "start".to_string(),
+        params: vec!["argc".to_string()],
+    });
+
+    let structure = Declaration::Struct { struct_name: "Point".to_string() };
+    assert_eq!(structure.with_name("Vector".to_string()), Declaration::Struct { struct_name: "Vector".to_string() });
+
+    let constant = Declaration::Const("MAX".to_string(), 10);
+    assert_eq!(constant.with_name("MIN".to_string()), Declaration::Const("MIN".to_string(), 10));
+
+    assert_eq!(Declaration::Marker.with_name("other".to_string()), Declaration::Marker);
+}
+
+#[test]
+fn set_reports_unchanged_variants() {
+    let mut structure = Declaration::Struct { struct_name: "Point".to_string() };
+    assert!(structure.set_name("Vector".to_string()));
+    assert_eq!(structure, Declaration::Struct { struct_name: "Vector".to_string() });
+
+    let mut marker = Declaration::Marker;
+    assert!(!marker.set_name("other".to_string()));
+
+    let mut anonymous = Declaration::Anonymous { params: vec![] };
+    assert!(!anonymous.set_name("lambda".to_string()));
+    assert_eq!(anonymous, Declaration::Anonymous { params: vec![] });
+}